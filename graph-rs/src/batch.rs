@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, NodeType, OverflowMode};
+
+impl<V: Value> Builder<V> {
+    /// Fills in the graph once for every input assignment in `inputs`.
+    ///
+    /// The graph is ordered and walked once, with values stored column-major:
+    /// each node holds one column of values across the batch, so it is
+    /// dispatched once and its arithmetic runs as a single loop over the
+    /// members. Hints are still called once per member, with a witness
+    /// holding their dependencies. Results are returned in the order of
+    /// `inputs`, and an assignment that fails (for example because it misses
+    /// an input or divides by zero) gets its own error without affecting the
+    /// rest of the batch.
+    pub fn fill_nodes_batch(
+        &self,
+        inputs: &[HashMap<usize, V>],
+        mode: OverflowMode,
+    ) -> Vec<Result<Witness<V>, GraphError>> {
+        let order = match self.topological_order() {
+            Ok(order) => order,
+            Err(error) => return vec![Err(error); inputs.len()],
+        };
+        
+        // The first error of each member; failed members carry placeholder values
+        let mut errors: Vec<Option<GraphError>> = inputs
+            .iter()
+            .map(|assignment| self.seed_witness(assignment.clone(), Witness::sparse()).err())
+            .collect();
+        let mut columns: Vec<Vec<V>> = vec![Vec::new(); self.next_id];
+        
+        for index in order {
+            let node = &self.nodes[index];
+            let operands: Result<Vec<&[V]>, GraphError> = node
+                .node_type
+                .operands()
+                .iter()
+                .map(|&id| match columns.get(id) {
+                    None => Err(GraphError::UnknownNode { node: id }),
+                    Some(column) if column.len() < inputs.len() => Err(GraphError::MissingOperand {
+                        node: node.id,
+                        operand: id,
+                    }),
+                    Some(column) => Ok(column.as_slice()),
+                })
+                .collect();
+            let operands = match operands {
+                Ok(operands) => operands,
+                Err(error) => {
+                    for slot in errors.iter_mut().filter(|slot| slot.is_none()) {
+                        *slot = Some(error.clone());
+                    }
+                    columns[node.id] = vec![V::zero(); inputs.len()];
+                    continue;
+                }
+            };
+            
+            let results: Vec<Result<V, GraphError>> = match &node.node_type {
+                NodeType::Input => inputs
+                    .iter()
+                    .map(|assignment| Ok(assignment.get(&node.id).cloned().unwrap_or_else(V::zero)))
+                    .collect(),
+                NodeType::Constant(value) => vec![Ok(value.clone()); inputs.len()],
+                NodeType::Add(..) => operands[0].iter().zip(operands[1]).map(|(a, b)| mode.add(node.id, a, b)).collect(),
+                NodeType::Sub(..) => operands[0].iter().zip(operands[1]).map(|(a, b)| mode.sub(node.id, a, b)).collect(),
+                NodeType::Mul(..) => operands[0].iter().zip(operands[1]).map(|(a, b)| mode.mul(node.id, a, b)).collect(),
+                NodeType::Neg(_) => operands[0].iter().map(|a| mode.neg(node.id, a)).collect(),
+                NodeType::Hint(deps, func) => (0..inputs.len())
+                    .map(|member| {
+                        if errors[member].is_some() {
+                            return Ok(V::zero());
+                        }
+                        let mut values = Witness::sparse();
+                        for (dep, column) in deps.iter().zip(&operands) {
+                            values.insert(*dep, column[member].clone());
+                        }
+                        func.call(node.id, &values)
+                    })
+                    .collect(),
+            };
+            
+            // Keep the first error of each member
+            let column = results
+                .into_iter()
+                .zip(errors.iter_mut())
+                .map(|(result, slot)| {
+                    result.unwrap_or_else(|error| {
+                        slot.get_or_insert(error);
+                        V::zero()
+                    })
+                })
+                .collect();
+            columns[node.id] = column;
+        }
+        
+        errors
+            .into_iter()
+            .enumerate()
+            .map(|(member, error)| match error {
+                Some(error) => Err(error),
+                None => {
+                    let mut values = Witness::with_len(self.next_id);
+                    for (id, column) in columns.iter().enumerate() {
+                        if let Some(value) = column.get(member) {
+                            values.insert(id, value.clone());
+                        }
+                    }
+                    Ok(values)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{BabyBear, Fp};
+
+    #[test]
+    fn test_batch_matches_single_evaluation() {
+        // f(x, y) = (x * y) + (x / y)
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_times_y = builder.mul(x.clone(), y.clone()).unwrap();
+        let x_div_y = builder.div(x.clone(), y.clone()).unwrap();
+        let _result = builder.add(x_times_y, x_div_y).unwrap();
+
+        let inputs: Vec<_> = (1..20)
+            .map(|i| HashMap::from([(x.id(), Fp::new(i * 7)), (y.id(), Fp::new(i))]))
+            .collect();
+        let batch = builder.fill_nodes_batch(&inputs, OverflowMode::Wrapping);
+
+        assert_eq!(batch.len(), inputs.len());
+        for (assignment, result) in inputs.iter().zip(batch) {
+            let values = result.unwrap();
+            assert_eq!(values, builder.fill_nodes(assignment.clone()).unwrap());
+            assert!(builder.check_constraints(&values));
+        }
+    }
+
+    #[test]
+    fn test_batch_errors_are_per_member() {
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let quotient = builder.div(x.clone(), y.clone()).unwrap();
+
+        let inputs = vec![
+            HashMap::from([(x.id(), Fp::new(6)), (y.id(), Fp::new(3))]),
+            HashMap::from([(x.id(), Fp::new(6))]),
+            HashMap::from([(x.id(), Fp::new(6)), (y.id(), Fp::zero())]),
+            HashMap::from([(x.id(), Fp::new(6)), (y.id(), Fp::new(2))]),
+        ];
+        let batch = builder.fill_nodes_batch(&inputs, OverflowMode::Wrapping);
+
+        assert_eq!(batch[0].as_ref().unwrap()[&quotient], Fp::new(2));
+        assert_eq!(batch[1], Err(GraphError::MissingInput { node: y.id() }));
+        assert_eq!(batch[2], Err(GraphError::DivisionByZero { node: 2, divisor: y.id() }));
+        assert_eq!(batch[3].as_ref().unwrap()[&quotient], Fp::new(3));
+    }
+
+    #[test]
+    fn test_hints_skip_failed_members() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let quotient = builder.div(x.clone(), y.clone()).unwrap();
+        let counter = Arc::clone(&calls);
+        let quotient_id = quotient.id();
+        let doubled = builder.hint(vec![quotient.clone()], move |values| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            values[quotient_id] + values[quotient_id]
+        }).unwrap();
+
+        let inputs: Vec<_> = [3, 0, 2]
+            .into_iter()
+            .map(|divisor| HashMap::from([(x.id(), Fp::new(6)), (y.id(), Fp::new(divisor))]))
+            .collect();
+        let batch = builder.fill_nodes_batch(&inputs, OverflowMode::Wrapping);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(batch[0].as_ref().unwrap()[&doubled], Fp::new(4));
+        assert!(batch[1].is_err());
+        assert_eq!(batch[2].as_ref().unwrap()[&doubled], Fp::new(6));
+    }
+
+    #[test]
+    fn test_batch_overflow_mode() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let _square = builder.mul(x.clone(), x.clone()).unwrap();
+
+        let inputs = vec![HashMap::from([(x.id(), 3)]), HashMap::from([(x.id(), 1 << 16)])];
+        let batch = builder.fill_nodes_batch(&inputs, OverflowMode::Checked);
+
+        assert_eq!(batch[0].as_ref().unwrap()[1], 9);
+        assert!(matches!(batch[1], Err(GraphError::Overflow { node: 1, .. })));
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, Node, NodeType, OverflowMode};
+
+impl<V: Value> Builder<V> {
+    /// Evaluates only the nodes that `targets` depend on.
+    ///
+    /// Inputs are required only if they are in the dependency cone of a
+    /// target, and nodes outside the cone, hints included, are never
+    /// evaluated. The returned witness holds values for the cone alone.
+    pub fn fill_nodes_for(
+        &self,
+        targets: &[Node<V>],
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+    ) -> Result<Witness<V>, GraphError> {
+        let in_cone = self.cone(targets)?;
+        
+        let mut values = Witness::with_len(self.next_id);
+        for (index, node) in self.nodes.iter().enumerate() {
+            if in_cone[index] {
+                if let NodeType::Input = node.node_type {
+                    match inputs.get(&node.id) {
+                        Some(value) => values.insert(node.id, value.clone()),
+                        None => return Err(GraphError::MissingInput { node: node.id }),
+                    };
+                }
+            }
+        }
+        
+        for index in self.topological_order()? {
+            let node = &self.nodes[index];
+            if !in_cone[index] || matches!(node.node_type, NodeType::Input) {
+                continue;
+            }
+            let value = self.eval_node(node, &values, mode)?;
+            values.insert(node.id, value);
+        }
+        
+        Ok(values)
+    }
+
+    /// Marks, by index into `self.nodes`, every node that one of `targets` depends on.
+    fn cone(&self, targets: &[Node<V>]) -> Result<Vec<bool>, GraphError> {
+        let operands = self.operand_indices();
+        let mut in_cone = vec![false; self.nodes.len()];
+        let mut stack = Vec::new();
+        
+        for target in targets {
+            self.check(target)?;
+            match self.nodes.iter().position(|node| node.id == target.id) {
+                Some(index) => stack.push(index),
+                None => return Err(GraphError::UnknownNode { node: target.id }),
+            }
+        }
+        while let Some(index) = stack.pop() {
+            if in_cone[index] {
+                continue;
+            }
+            in_cone[index] = true;
+            stack.extend(operands[index].iter().copied().filter(|&operand| !in_cone[operand]));
+        }
+        
+        Ok(in_cone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_only_cone_is_evaluated() {
+        let expensive_calls = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<u64>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let five = builder.constant(5);
+        let target = builder.add(x_squared.clone(), five).unwrap();
+        let expensive = {
+            let calls = Arc::clone(&expensive_calls);
+            builder.hint(vec![y.clone()], move |values| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                values[1] + 1
+            }).unwrap()
+        };
+        let _other = builder.mul(expensive, target.clone()).unwrap();
+
+        // y is outside the cone of target, so it does not need a value
+        let values = builder
+            .fill_nodes_for(std::slice::from_ref(&target), HashMap::from([(x.id(), 3)]), OverflowMode::Wrapping)
+            .unwrap();
+
+        assert_eq!(values[&target], 14);
+        assert_eq!(values.iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![0, 2, 3, 4]);
+        assert_eq!(expensive_calls.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_cone_inputs_are_required() {
+        let mut builder = Builder::<u64>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let sum = builder.add(x.clone(), y.clone()).unwrap();
+        let _double_x = builder.add(x.clone(), x.clone()).unwrap();
+
+        let error = builder
+            .fill_nodes_for(&[sum], HashMap::from([(x.id(), 1)]), OverflowMode::Wrapping)
+            .unwrap_err();
+        assert_eq!(error, GraphError::MissingInput { node: y.id() });
+    }
+
+    #[test]
+    fn test_unknown_target() {
+        let mut builder = Builder::<u64>::new();
+        let _x = builder.init();
+        let dropped = builder.init();
+        builder.nodes.pop();
+
+        // The id is in range, but no node of the graph has it
+        let error = builder
+            .fill_nodes_for(&[dropped], HashMap::new(), OverflowMode::Wrapping)
+            .unwrap_err();
+        assert_eq!(error, GraphError::UnknownNode { node: 1 });
+    }
+
+    #[test]
+    fn test_foreign_target() {
+        let mut other = Builder::<u64>::new();
+        let _ = other.init();
+        let foreign = other.init();
+
+        let mut builder = Builder::<u64>::new();
+        let _x = builder.init();
+        let error = builder
+            .fill_nodes_for(&[foreign], HashMap::new(), OverflowMode::Wrapping)
+            .unwrap_err();
+        assert_eq!(error, GraphError::ForeignNode { node: 1 });
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+use crate::error::GraphError;
+use crate::gate::CustomGate;
+use crate::value::{Field, Value};
+use crate::lookup::Table;
+use crate::witness::Witness;
+use crate::{Builder, ConstraintId, Node};
+
+/// What a constraint requires of the nodes it names.
+///
+/// Every kind has a lowering to equalities and multiplications, listed on
+/// each variant, so exporters that only support `a == b` can handle all of
+/// them; `Builder::lower_constraints` applies it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// `a == b`. Already an equality.
+    Equal(usize, usize),
+    /// `a == 0`. Lowers to `a == c` for a constant node `c = 0`.
+    Zero(usize),
+    /// `a` is 0 or 1. Lowers to `a * a == a`.
+    Bool(usize),
+    /// `a != 0`. Lowers to `a * inverse == 1` for a hinted `inverse`, which
+    /// has no solution when `a` is zero.
+    NonZero(usize),
+    /// `a != b`. Lowers to the `NonZero` lowering of `a - b`.
+    NotEqual(usize, usize),
+    /// The values of `nodes`, in order, form a row of table `table`. Not
+    /// lowered: exporters emit it as a lookup argument.
+    Lookup { table: usize, nodes: Vec<usize> },
+    /// Custom gate `gate` evaluates to zero with its wires set to `nodes`, in
+    /// order. Not lowered: exporters emit the polynomial identity as declared.
+    Gate { gate: usize, nodes: Vec<usize> },
+}
+
+impl ConstraintKind {
+    /// Returns the ids of the nodes the constraint names.
+    pub fn nodes(&self) -> Vec<usize> {
+        match self {
+            ConstraintKind::Equal(a, b) | ConstraintKind::NotEqual(a, b) => vec![*a, *b],
+            ConstraintKind::Zero(a) | ConstraintKind::Bool(a) | ConstraintKind::NonZero(a) => vec![*a],
+            ConstraintKind::Lookup { nodes, .. } | ConstraintKind::Gate { nodes, .. } => nodes.clone(),
+        }
+    }
+
+    /// Returns true if the constraint holds for `values`, looking up rows in
+    /// `tables` and polynomials in `gates`. Nodes without a value never satisfy it.
+    pub fn holds<V: Value>(&self, values: &Witness<V>, tables: &[Table<V>], gates: &[CustomGate<V>]) -> bool {
+        let value = |id: &usize| values.get(*id);
+        match self {
+            ConstraintKind::Equal(a, b) => matches!((value(a), value(b)), (Some(a), Some(b)) if a == b),
+            ConstraintKind::NotEqual(a, b) => matches!((value(a), value(b)), (Some(a), Some(b)) if a != b),
+            ConstraintKind::Zero(a) => value(a).is_some_and(|a| *a == V::zero()),
+            ConstraintKind::Bool(a) => value(a).is_some_and(|a| *a == V::zero() || *a == V::one()),
+            ConstraintKind::NonZero(a) => value(a).is_some_and(|a| *a != V::zero()),
+            ConstraintKind::Lookup { table, nodes } => {
+                let tuple: Option<Vec<&V>> = nodes.iter().map(value).collect();
+                matches!((tables.get(*table), tuple), (Some(table), Some(tuple)) if table.contains(&tuple))
+            }
+            ConstraintKind::Gate { gate, nodes } => {
+                let wires: Option<Vec<&V>> = nodes.iter().map(value).collect();
+                matches!(
+                    (gates.get(*gate), wires),
+                    (Some(gate), Some(wires)) if gate.evaluate(&wires) == Some(V::zero())
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConstraintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintKind::Equal(a, b) => write!(f, "{} == {}", a, b),
+            ConstraintKind::Zero(a) => write!(f, "{} == 0", a),
+            ConstraintKind::Bool(a) => write!(f, "{} in {{0, 1}}", a),
+            ConstraintKind::NonZero(a) => write!(f, "{} != 0", a),
+            ConstraintKind::NotEqual(a, b) => write!(f, "{} != {}", a, b),
+            ConstraintKind::Lookup { table, nodes } => write!(f, "{:?} in table {}", nodes, table),
+            ConstraintKind::Gate { gate, nodes } => write!(f, "gate {} on {:?}", gate, nodes),
+        }
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Asserts that a node is zero.
+    pub fn assert_zero(&mut self, a: Node<V>) -> Result<ConstraintId, GraphError> {
+        let a = self.check(&a)?;
+        Ok(self.push_constraint(ConstraintKind::Zero(a), None))
+    }
+
+    /// Asserts that a node is 0 or 1.
+    pub fn assert_bool(&mut self, a: Node<V>) -> Result<ConstraintId, GraphError> {
+        let a = self.check(&a)?;
+        Ok(self.push_constraint(ConstraintKind::Bool(a), None))
+    }
+
+    /// Asserts that a node is not zero.
+    pub fn assert_nonzero(&mut self, a: Node<V>) -> Result<ConstraintId, GraphError> {
+        let a = self.check(&a)?;
+        Ok(self.push_constraint(ConstraintKind::NonZero(a), None))
+    }
+
+    /// Asserts that 2 nodes are not equal.
+    pub fn assert_not_equal(&mut self, a: Node<V>, b: Node<V>) -> Result<ConstraintId, GraphError> {
+        let (a, b) = (self.check(&a)?, self.check(&b)?);
+        Ok(self.push_constraint(ConstraintKind::NotEqual(a, b), None))
+    }
+
+    /// Names a constraint in violation reports. The label carries over to
+    /// whatever `lower_constraints` replaces the constraint with.
+    pub fn label(&mut self, constraint: ConstraintId, label: &str) -> Result<(), GraphError> {
+        match self.constraints.get_mut(constraint.index) {
+            Some(found) if constraint.builder == self.id => {
+                found.label = Some(label.to_string());
+                Ok(())
+            }
+            _ => Err(GraphError::ForeignConstraint { constraint: constraint.index }),
+        }
+    }
+
+    /// Returns the constraints in the order they were added.
+    pub fn constraints(&self) -> impl Iterator<Item = &ConstraintKind> {
+        self.constraints.iter().map(|constraint| &constraint.kind)
+    }
+}
+
+impl<V: Field> Builder<V> {
+    /// Returns a copy of the graph in which every constraint is an `Equal`, a
+    /// `Lookup` or a `Gate`, following the lowering documented on `ConstraintKind`.
+    ///
+    /// Existing node ids are unchanged and the nodes the lowering needs are
+    /// appended; handles from this builder go through `adopt` on the copy.
+    /// Labels carry over to the equality that replaces each constraint.
+    pub fn lower_constraints(&self) -> Result<Builder<V>, GraphError> {
+        let mut lowered = self.derive();
+        lowered.constraints.clear();
+        
+        for constraint in &self.constraints {
+            match constraint.kind {
+                ConstraintKind::Equal(..) | ConstraintKind::Lookup { .. } | ConstraintKind::Gate { .. } => {
+                    lowered.constraints.push(constraint.clone())
+                }
+                ConstraintKind::Zero(a) => {
+                    let zero = lowered.constant(V::zero());
+                    lowered.push_constraint(ConstraintKind::Equal(a, zero.id), constraint.label.clone());
+                }
+                ConstraintKind::Bool(a) => {
+                    let a = lowered.handle(a)?;
+                    let square = lowered.mul(a.clone(), a.clone())?;
+                    lowered.push_constraint(ConstraintKind::Equal(square.id, a.id), constraint.label.clone());
+                }
+                ConstraintKind::NonZero(a) => {
+                    let a = lowered.handle(a)?;
+                    lowered.inv(a)?;
+                    lowered.constraints.last_mut().unwrap().label = constraint.label.clone();
+                }
+                ConstraintKind::NotEqual(a, b) => {
+                    let (a, b) = (lowered.handle(a)?, lowered.handle(b)?);
+                    let difference = lowered.sub(a, b)?;
+                    lowered.inv(difference)?;
+                    lowered.constraints.last_mut().unwrap().label = constraint.label.clone();
+                }
+            }
+        }
+        
+        Ok(lowered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{BabyBear, Fp, OverflowMode};
+
+    type F = Fp<BabyBear>;
+
+    /// Constrains x to be boolean, y non-zero, z zero and x != y.
+    fn example() -> (Builder<F>, [Node<F>; 3]) {
+        let mut builder = Builder::new();
+        let x = builder.init();
+        let y = builder.init();
+        let z = builder.init();
+        builder.assert_bool(x.clone()).unwrap();
+        builder.assert_nonzero(y.clone()).unwrap();
+        builder.assert_zero(z.clone()).unwrap();
+        builder.assert_not_equal(x.clone(), y.clone()).unwrap();
+        (builder, [x, y, z])
+    }
+
+    fn inputs(x: u64, y: u64, z: u64) -> HashMap<usize, F> {
+        HashMap::from([(0, Fp::new(x)), (1, Fp::new(y)), (2, Fp::new(z))])
+    }
+
+    #[test]
+    fn test_check_constraints() {
+        let (builder, _) = example();
+        let check = |x, y, z| builder.check_constraints(&builder.fill_nodes(inputs(x, y, z)).unwrap());
+
+        assert!(check(1, 5, 0));
+        assert!(check(0, 1, 0));
+        assert!(!check(2, 5, 0));
+        assert!(!check(1, 0, 0));
+        assert!(!check(1, 5, 3));
+        assert!(!check(1, 1, 0));
+        assert_eq!(
+            builder.constraints().map(|kind| kind.to_string()).collect::<Vec<_>>(),
+            vec!["0 in {0, 1}", "1 != 0", "2 == 0", "0 != 1"]
+        );
+    }
+
+    #[test]
+    fn test_labels_survive_lowering() {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let flag = builder.assert_bool(x.clone()).unwrap();
+        let distinct = builder.assert_not_equal(x.clone(), y.clone()).unwrap();
+        builder.label(flag, "x is a flag").unwrap();
+        builder.label(distinct, "x and y differ").unwrap();
+
+        let lowered = builder.lower_constraints().unwrap();
+        let values = lowered.fill_nodes(inputs(2, 5, 0)).unwrap();
+        let labels: Vec<_> = lowered
+            .constraint_violations(&values)
+            .into_iter()
+            .map(|violation| violation.label)
+            .collect();
+        assert_eq!(labels, vec![Some("x is a flag".to_string())]);
+        assert_eq!(
+            lowered.constraints.iter().map(|constraint| constraint.label.as_deref()).collect::<Vec<_>>(),
+            vec![Some("x is a flag"), Some("x and y differ")]
+        );
+
+        // Handles do not carry over to other builders, lowered copies included
+        let mut lowered = lowered;
+        assert_eq!(lowered.label(flag, "stale").unwrap_err(), GraphError::ForeignConstraint { constraint: 0 });
+    }
+
+    #[test]
+    fn test_lowering_agrees() {
+        let (builder, [x, y, _]) = example();
+        let lowered = builder.lower_constraints().unwrap();
+        assert!(lowered.constraints().all(|kind| matches!(kind, ConstraintKind::Equal(..))));
+        assert_eq!(lowered.constraints().count(), 4);
+
+        for (x_value, y_value, z_value) in [(1, 5, 0), (0, 1, 0), (2, 5, 0), (1, 5, 3)] {
+            let values = builder.fill_nodes(inputs(x_value, y_value, z_value)).unwrap();
+            let lowered_values = lowered.fill_nodes(inputs(x_value, y_value, z_value)).unwrap();
+            assert_eq!(builder.check_constraints(&values), lowered.check_constraints(&lowered_values));
+        }
+
+        // A zero where the lowering needs an inverse cannot be witnessed at all
+        assert!(matches!(
+            lowered.fill_nodes(inputs(1, 1, 0)),
+            Err(GraphError::DivisionByZero { .. })
+        ));
+
+        // Original handles work on the lowered graph once adopted
+        let mut lowered = lowered;
+        assert_eq!(lowered.add(x.clone(), y.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id() });
+        let (x_adopted, y_adopted) = (lowered.adopt(&x).unwrap(), lowered.adopt(&y).unwrap());
+        let sum = lowered.add(x_adopted, y_adopted).unwrap();
+        let values = lowered.fill_nodes_with_mode(inputs(1, 5, 0), OverflowMode::Checked).unwrap();
+        assert_eq!(values[&sum], Fp::new(6));
+
+        // A node the original adds afterwards may share an id with one of the copy's
+        let mut builder = builder;
+        let product = builder.mul(x, y).unwrap();
+        assert!(product.id() < lowered.next_id);
+        assert_eq!(lowered.neg(product.clone()).unwrap_err(), GraphError::ForeignNode { node: product.id() });
+        assert_eq!(lowered.adopt(&product).unwrap_err(), GraphError::ForeignNode { node: product.id() });
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::value::Value;
+use crate::Builder;
+
+/// How much a builder reports while evaluating.
+///
+/// Levels are ordered, and each includes the messages of the levels below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Report nothing.
+    #[default]
+    Silent,
+    /// Report evaluation errors along with the values computed so far.
+    Errors,
+    /// Also report every node of the graph before evaluating.
+    Trace,
+}
+
+/// A destination for diagnostic messages: the level of the message and its text.
+type Sink = dyn Fn(Verbosity, &str) + Send + Sync;
+
+/// Where and how much a builder reports. Silent unless configured.
+#[derive(Clone, Default)]
+pub(crate) struct Diagnostics {
+    verbosity: Verbosity,
+    // Standard error when unset
+    sink: Option<Arc<Sink>>,
+}
+
+impl Diagnostics {
+    /// Emits the message built by `message` if `level` is enabled.
+    pub(crate) fn emit(&self, level: Verbosity, message: impl FnOnce() -> String) {
+        if level == Verbosity::Silent || level > self.verbosity {
+            return;
+        }
+        match &self.sink {
+            Some(sink) => sink(level, &message()),
+            None => eprintln!("{}", message()),
+        }
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("verbosity", &self.verbosity)
+            .field("sink", &self.sink.as_ref().map(|_| "custom"))
+            .finish()
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Sets how much evaluation reports. Builders start out silent.
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) {
+        self.diagnostics.verbosity = verbosity;
+    }
+
+    /// Sends diagnostic messages to `sink` instead of standard error.
+    pub fn set_diagnostic_sink<F>(&mut self, sink: F)
+    where
+        F: Fn(Verbosity, &str) + Send + Sync + 'static,
+    {
+        self.diagnostics.sink = Some(Arc::new(sink));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Evaluates x - 3 with `x` as the input, or no input at all for `None`.
+    fn recorded(verbosity: Verbosity, x: Option<u32>) -> Vec<(Verbosity, String)> {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let mut builder = Builder::<u32>::new();
+        let x_node = builder.init();
+        let three = builder.constant(3);
+        let _difference = builder.sub(x_node.clone(), three).unwrap();
+        builder.set_verbosity(verbosity);
+        let sink = Arc::clone(&messages);
+        builder.set_diagnostic_sink(move |level, message| {
+            sink.lock().unwrap().push((level, message.to_string()));
+        });
+
+        let inputs = x.map(|x| HashMap::from([(x_node.id(), x)])).unwrap_or_default();
+        let _ = builder.fill_nodes_with_mode(inputs, crate::OverflowMode::Checked);
+        let messages = messages.lock().unwrap().clone();
+        messages
+    }
+
+    #[test]
+    fn test_silent_by_default() {
+        assert!(recorded(Verbosity::Silent, Some(1)).is_empty());
+        assert!(recorded(Verbosity::Errors, Some(5)).is_empty());
+    }
+
+    #[test]
+    fn test_levels() {
+        let errors = recorded(Verbosity::Errors, Some(1));
+        assert_eq!(
+            errors,
+            vec![
+                (Verbosity::Errors, "Sub overflowed at node 2 with operands 1, 3".to_string()),
+                (Verbosity::Errors, "Values map: {0: 1, 1: 3}".to_string()),
+            ]
+        );
+
+        let trace = recorded(Verbosity::Trace, Some(5));
+        assert_eq!(
+            trace,
+            vec![
+                (Verbosity::Trace, "Node 0: Node(0, Input)".to_string()),
+                (Verbosity::Trace, "Node 1: Node(1, Constant(3))".to_string()),
+                (Verbosity::Trace, "Node 2: Node(2, Sub(0, 1))".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_errors_are_reported() {
+        assert!(recorded(Verbosity::Silent, None).is_empty());
+        assert_eq!(
+            recorded(Verbosity::Errors, None),
+            vec![(Verbosity::Errors, "Missing value for input node 0".to_string())]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::fmt;
+
+/// An error raised while evaluating a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// An input node was not given a value.
+    MissingInput { node: usize },
+    /// A node was evaluated before one of its operands had a value.
+    MissingOperand { node: usize, operand: usize },
+    /// A hint function failed to produce a value.
+    HintFailed {
+        node: usize,
+        hint: usize,
+        message: String,
+    },
+    /// A division or inversion node was evaluated with a zero divisor.
+    DivisionByZero { node: usize, divisor: usize },
+    /// An arithmetic node overflowed during checked evaluation.
+    Overflow {
+        node: usize,
+        operation: &'static str,
+        operands: Vec<String>,
+    },
+    /// A node id does not belong to the graph.
+    UnknownNode { node: usize },
+    /// A node handle was created by a different builder.
+    ForeignNode { node: usize },
+    /// A constraint handle was returned by a different builder.
+    ForeignConstraint { constraint: usize },
+    /// A table id does not belong to the builder.
+    UnknownTable { table: usize },
+    /// A table id was returned by a different builder.
+    ForeignTable { table: usize },
+    /// A table row or lookup does not have as many entries as the table is wide.
+    TableWidth {
+        table: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A range check is too wide for the field: `2^bits` is not below the modulus.
+    RangeTooWide { bits: u32, modulus_bits: u32 },
+    /// A gate id does not belong to the builder.
+    UnknownGate { gate: usize },
+    /// A gate id was returned by a different builder.
+    ForeignGate { gate: usize },
+    /// A gate term uses a wire at or beyond the gate's wire count.
+    UnknownWire { gate: usize, wire: usize },
+    /// A gate was applied to a different number of nodes than it has wires.
+    GateArity {
+        gate: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A value was supplied for a node that is not an input node.
+    NotAnInput { node: usize },
+    /// The graph contains a cycle through the listed nodes, each reading the next.
+    Cycle { nodes: Vec<usize> },
+    /// Serialized program bytes are malformed at the given offset.
+    InvalidProgram { offset: usize },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::MissingInput { node } => {
+                write!(f, "Missing value for input node {}", node)
+            }
+            GraphError::MissingOperand { node, operand } => {
+                write!(f, "Missing value of operand {} at node {}", operand, node)
+            }
+            GraphError::HintFailed { node, hint, message } => {
+                write!(f, "Hint {} failed at node {}: {}", hint, node, message)
+            }
+            GraphError::DivisionByZero { node, divisor } => {
+                write!(f, "Division by zero at node {}: node {} is zero", node, divisor)
+            }
+            GraphError::Overflow { node, operation, operands } => {
+                write!(f, "{} overflowed at node {} with operands {}", operation, node, operands.join(", "))
+            }
+            GraphError::UnknownNode { node } => {
+                write!(f, "Node {} is not part of the graph", node)
+            }
+            GraphError::ForeignNode { node } => {
+                write!(f, "Node {} belongs to a different builder", node)
+            }
+            GraphError::ForeignConstraint { constraint } => {
+                write!(f, "Constraint {} belongs to a different builder", constraint)
+            }
+            GraphError::UnknownTable { table } => {
+                write!(f, "Table {} is not registered with the builder", table)
+            }
+            GraphError::ForeignTable { table } => {
+                write!(f, "Table {} belongs to a different builder", table)
+            }
+            GraphError::TableWidth { table, expected, found } => {
+                write!(f, "Table {} has {} columns, but {} entries were given", table, expected, found)
+            }
+            GraphError::RangeTooWide { bits, modulus_bits } => {
+                write!(f, "A range of {} bits does not fit below a {}-bit modulus", bits, modulus_bits)
+            }
+            GraphError::UnknownGate { gate } => {
+                write!(f, "Gate {} is not registered with the builder", gate)
+            }
+            GraphError::ForeignGate { gate } => {
+                write!(f, "Gate {} belongs to a different builder", gate)
+            }
+            GraphError::UnknownWire { gate, wire } => {
+                write!(f, "Gate {} uses wire {}, which it does not have", gate, wire)
+            }
+            GraphError::GateArity { gate, expected, found } => {
+                write!(f, "Gate {} has {} wires, but {} nodes were given", gate, expected, found)
+            }
+            GraphError::NotAnInput { node } => {
+                write!(f, "Node {} is not an input node", node)
+            }
+            GraphError::Cycle { nodes } => {
+                write!(f, "Cycle through nodes {:?}", nodes)
+            }
+            GraphError::InvalidProgram { offset } => {
+                write!(f, "Invalid program bytes at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
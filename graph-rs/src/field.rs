@@ -0,0 +1,266 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::value::{Field, Value};
+
+/// A prime modulus that defines a field `Fp<M>`.
+pub trait PrimeModulus: Copy + fmt::Debug + PartialEq + Eq + Send + Sync + 'static {
+    /// The prime. Must be greater than 2 and fit in a `u64`.
+    const MODULUS: u64;
+}
+
+/// The Goldilocks prime, 2^64 - 2^32 + 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goldilocks;
+
+impl PrimeModulus for Goldilocks {
+    const MODULUS: u64 = 0xffff_ffff_0000_0001;
+}
+
+/// The BabyBear prime, 2^31 - 2^27 + 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BabyBear;
+
+impl PrimeModulus for BabyBear {
+    const MODULUS: u64 = 0x7800_0001;
+}
+
+/// The Mersenne prime 2^31 - 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mersenne31;
+
+impl PrimeModulus for Mersenne31 {
+    const MODULUS: u64 = 0x7fff_ffff;
+}
+
+/// An element of the prime field defined by `M`, kept in canonical form `[0, p)`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Fp<M: PrimeModulus> {
+    value: u64,
+    modulus: PhantomData<M>,
+}
+
+impl<M: PrimeModulus> Fp<M> {
+    /// Creates a field element, reducing `value` modulo the prime.
+    pub fn new(value: u64) -> Self {
+        Fp {
+            value: value % M::MODULUS,
+            modulus: PhantomData,
+        }
+    }
+
+    /// The additive identity.
+    pub fn zero() -> Self {
+        Self::new(0)
+    }
+
+    /// The multiplicative identity.
+    pub fn one() -> Self {
+        Self::new(1)
+    }
+
+    /// Returns the canonical representative in `[0, p)`.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Returns true if this is the zero element.
+    pub fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    /// Raises this element to the power `exp`.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut base = *self;
+        let mut result = Self::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse, or `None` for zero.
+    pub fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        // Fermat's little theorem: a^(p-2) = a^-1
+        Some(self.pow(M::MODULUS - 2))
+    }
+}
+
+impl<M: PrimeModulus> Value for Fp<M> {
+    fn zero() -> Self {
+        Fp::zero()
+    }
+
+    fn one() -> Self {
+        Fp::one()
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Fp::new(value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        *self + *other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        *self * *other
+    }
+
+    fn neg(&self) -> Self {
+        -*self
+    }
+
+    fn bit(&self, i: u32) -> Option<bool> {
+        self.value.bit(i)
+    }
+
+    fn modulus_bits() -> Option<u32> {
+        Some(u64::BITS - M::MODULUS.leading_zeros())
+    }
+}
+
+impl<M: PrimeModulus> Field for Fp<M> {
+    fn inverse(&self) -> Option<Self> {
+        Fp::inverse(self)
+    }
+}
+
+impl<M: PrimeModulus> From<u64> for Fp<M> {
+    fn from(value: u64) -> Self {
+        Fp::new(value)
+    }
+}
+
+impl<M: PrimeModulus> From<u32> for Fp<M> {
+    fn from(value: u32) -> Self {
+        Fp::new(value as u64)
+    }
+}
+
+impl<M: PrimeModulus> Add for Fp<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = self.value as u128 + other.value as u128;
+        Fp {
+            value: (sum % M::MODULUS as u128) as u64,
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<M: PrimeModulus> Sub for Fp<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let value = if self.value >= other.value {
+            self.value - other.value
+        } else {
+            M::MODULUS - (other.value - self.value)
+        };
+        Fp {
+            value,
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<M: PrimeModulus> Mul for Fp<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let product = self.value as u128 * other.value as u128;
+        Fp {
+            value: (product % M::MODULUS as u128) as u64,
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<M: PrimeModulus> Neg for Fp<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl<M: PrimeModulus> fmt::Debug for Fp<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<M: PrimeModulus> fmt::Display for Fp<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction_and_wraparound() {
+        type F = Fp<BabyBear>;
+        let p = BabyBear::MODULUS;
+
+        assert_eq!(F::new(p), F::zero());
+        assert_eq!(F::new(p + 5).value(), 5);
+        assert_eq!((F::new(p - 1) + F::new(2)).value(), 1);
+        assert_eq!((F::new(1) - F::new(2)).value(), p - 1);
+        assert_eq!((-F::one()).value(), p - 1);
+    }
+
+    #[test]
+    fn test_goldilocks_does_not_overflow() {
+        type F = Fp<Goldilocks>;
+        let minus_one = F::new(Goldilocks::MODULUS - 1);
+
+        assert_eq!(minus_one + minus_one, F::new(Goldilocks::MODULUS - 2));
+        assert_eq!(minus_one * minus_one, F::one());
+    }
+
+    #[test]
+    fn test_inverse() {
+        fn check<M: PrimeModulus>() {
+            for value in [1u64, 2, 8, 12345, M::MODULUS - 1] {
+                let a = Fp::<M>::new(value);
+                assert_eq!(a * a.inverse().unwrap(), Fp::one());
+            }
+            assert!(Fp::<M>::zero().inverse().is_none());
+        }
+
+        check::<Goldilocks>();
+        check::<BabyBear>();
+        check::<Mersenne31>();
+    }
+
+    #[test]
+    fn test_division_is_unique() {
+        // Unlike u32 wrapping arithmetic, c * 8 == b has exactly one solution.
+        type F = Fp<Mersenne31>;
+        let b = F::new(16);
+        let eight = F::new(8);
+        let c = b * eight.inverse().unwrap();
+
+        assert_eq!(c, F::new(2));
+        for candidate in [2u64, 2 + (1 << 29), 2 + (1 << 30)] {
+            assert_eq!(F::new(candidate) * eight == b, candidate == 2);
+        }
+    }
+}
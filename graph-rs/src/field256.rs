@@ -0,0 +1,454 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::value::{Field, Value};
+
+/// A prime modulus of up to 255 bits that defines a field `Fp256<P>`.
+///
+/// Only `MODULUS` has to be provided; the Montgomery constants are derived
+/// from it at compile time.
+pub trait PrimeModulus256: Copy + fmt::Debug + PartialEq + Eq + Send + Sync + 'static {
+    /// The prime as little-endian 64-bit limbs. Must be odd and below 2^255.
+    const MODULUS: [u64; 4];
+
+    /// -p^-1 mod 2^64, used by Montgomery reduction.
+    const INV: u64 = compute_inv(Self::MODULUS[0]);
+
+    /// R = 2^256 mod p, the Montgomery form of one.
+    const R: [u64; 4] = shift_left_mod(&[1, 0, 0, 0], 256, &Self::MODULUS);
+
+    /// R^2 mod p, used to convert into Montgomery form.
+    const R2: [u64; 4] = shift_left_mod(&Self::R, 256, &Self::MODULUS);
+}
+
+/// The scalar field of the BN254 (alt_bn128) curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bn254Scalar;
+
+impl PrimeModulus256 for Bn254Scalar {
+    // 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+    const MODULUS: [u64; 4] = [
+        0x43e1_f593_f000_0001,
+        0x2833_e848_79b9_7091,
+        0xb850_45b6_8181_585d,
+        0x3064_4e72_e131_a029,
+    ];
+}
+
+/// The scalar field of the BLS12-381 curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bls12381Scalar;
+
+impl PrimeModulus256 for Bls12381Scalar {
+    // 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001
+    const MODULUS: [u64; 4] = [
+        0xffff_ffff_0000_0001,
+        0x53bd_a402_fffe_5bfe,
+        0x3339_d808_09a1_d805,
+        0x73ed_a753_299d_7d48,
+    ];
+}
+
+/// An element of the 256-bit prime field defined by `P`, stored in Montgomery form.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Fp256<P: PrimeModulus256> {
+    // a * R mod p, fully reduced so equality is equality of field elements
+    limbs: [u64; 4],
+    modulus: PhantomData<P>,
+}
+
+impl<P: PrimeModulus256> Fp256<P> {
+    /// The additive identity.
+    pub fn zero() -> Self {
+        Self::from_montgomery([0; 4])
+    }
+
+    /// The multiplicative identity.
+    pub fn one() -> Self {
+        Self::from_montgomery(P::R)
+    }
+
+    /// Creates a field element from a small integer.
+    pub fn new(value: u64) -> Self {
+        Self::from_canonical([value, 0, 0, 0]).unwrap()
+    }
+
+    /// Creates a field element from little-endian limbs, or `None` if the value is not below p.
+    pub fn from_canonical(limbs: [u64; 4]) -> Option<Self> {
+        if !less_than(&limbs, &P::MODULUS) {
+            return None;
+        }
+        Some(Self::from_montgomery(montgomery_mul(&limbs, &P::R2, &P::MODULUS, P::INV)))
+    }
+
+    /// Parses a number in the given radix (2 to 16), reducing it modulo p.
+    /// A `0x` prefix is accepted for radix 16.
+    pub fn from_str_radix(s: &str, radix: u32) -> Option<Self> {
+        let digits = if radix == 16 {
+            s.strip_prefix("0x").unwrap_or(s)
+        } else {
+            s
+        };
+        if digits.is_empty() || !(2..=16).contains(&radix) {
+            return None;
+        }
+
+        let base = Self::new(radix as u64);
+        let mut result = Self::zero();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            result = result * base + Self::new(digit as u64);
+        }
+        Some(result)
+    }
+
+    /// Returns the canonical representative in `[0, p)` as little-endian limbs.
+    pub fn to_canonical(&self) -> [u64; 4] {
+        montgomery_mul(&self.limbs, &[1, 0, 0, 0], &P::MODULUS, P::INV)
+    }
+
+    /// Returns true if this is the zero element.
+    pub fn is_zero(&self) -> bool {
+        self.limbs == [0; 4]
+    }
+
+    /// Raises this element to the power `exp`, given as little-endian limbs.
+    pub fn pow(&self, exp: &[u64; 4]) -> Self {
+        let mut result = Self::one();
+        for limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result * result;
+                if (limb >> bit) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse, or `None` for zero.
+    pub fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        // Fermat's little theorem: a^(p-2) = a^-1
+        let (exponent, _) = sub_with_borrow(&P::MODULUS, &[2, 0, 0, 0]);
+        Some(self.pow(&exponent))
+    }
+
+    fn from_montgomery(limbs: [u64; 4]) -> Self {
+        Fp256 {
+            limbs,
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<P: PrimeModulus256> Value for Fp256<P> {
+    fn zero() -> Self {
+        Fp256::zero()
+    }
+
+    fn one() -> Self {
+        Fp256::one()
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Fp256::new(value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        *self + *other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        *self * *other
+    }
+
+    fn neg(&self) -> Self {
+        -*self
+    }
+
+    fn bit(&self, i: u32) -> Option<bool> {
+        let limbs = self.to_canonical();
+        Some(limbs.get(i as usize / 64).is_some_and(|limb| (limb >> (i % 64)) & 1 == 1))
+    }
+
+    fn modulus_bits() -> Option<u32> {
+        let top = P::MODULUS.iter().rposition(|&limb| limb != 0)?;
+        Some(top as u32 * 64 + u64::BITS - P::MODULUS[top].leading_zeros())
+    }
+}
+
+impl<P: PrimeModulus256> Field for Fp256<P> {
+    fn inverse(&self) -> Option<Self> {
+        Fp256::inverse(self)
+    }
+}
+
+impl<P: PrimeModulus256> From<u64> for Fp256<P> {
+    fn from(value: u64) -> Self {
+        Fp256::new(value)
+    }
+}
+
+impl<P: PrimeModulus256> Add for Fp256<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        // Both operands are below p < 2^255, so the sum cannot carry out
+        let (sum, _) = add_with_carry(&self.limbs, &other.limbs);
+        Self::from_montgomery(reduce_once(sum, &P::MODULUS))
+    }
+}
+
+impl<P: PrimeModulus256> Sub for Fp256<P> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let (difference, borrow) = sub_with_borrow(&self.limbs, &other.limbs);
+        if borrow {
+            let (wrapped, _) = add_with_carry(&difference, &P::MODULUS);
+            Self::from_montgomery(wrapped)
+        } else {
+            Self::from_montgomery(difference)
+        }
+    }
+}
+
+impl<P: PrimeModulus256> Mul for Fp256<P> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_montgomery(montgomery_mul(&self.limbs, &other.limbs, &P::MODULUS, P::INV))
+    }
+}
+
+impl<P: PrimeModulus256> Neg for Fp256<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl<P: PrimeModulus256> fmt::Debug for Fp256<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl<P: PrimeModulus256> fmt::Display for Fp256<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Peel off base-10^19 chunks, least significant first
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut value = self.to_canonical();
+        let mut chunks = Vec::new();
+        loop {
+            let mut remainder = 0u128;
+            for limb in value.iter_mut().rev() {
+                let current = (remainder << 64) | *limb as u128;
+                *limb = (current / CHUNK as u128) as u64;
+                remainder = current % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+            if value == [0; 4] {
+                break;
+            }
+        }
+
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes -p^-1 mod 2^64 by Newton iteration on the lowest limb.
+const fn compute_inv(p0: u64) -> u64 {
+    let mut inv = 1u64;
+    let mut i = 0;
+    while i < 63 {
+        inv = inv.wrapping_mul(inv);
+        inv = inv.wrapping_mul(p0);
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+/// Computes value * 2^shift mod p by repeated doubling. `value` must be below p.
+const fn shift_left_mod(value: &[u64; 4], shift: usize, p: &[u64; 4]) -> [u64; 4] {
+    let mut result = *value;
+    let mut i = 0;
+    while i < shift {
+        let (doubled, _) = add_with_carry(&result, &result);
+        result = reduce_once(doubled, p);
+        i += 1;
+    }
+    result
+}
+
+const fn add_with_carry(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut result = [0u64; 4];
+    let mut carry = 0u64;
+    let mut i = 0;
+    while i < 4 {
+        let sum = a[i] as u128 + b[i] as u128 + carry as u128;
+        result[i] = sum as u64;
+        carry = (sum >> 64) as u64;
+        i += 1;
+    }
+    (result, carry != 0)
+}
+
+const fn sub_with_borrow(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut result = [0u64; 4];
+    let mut borrow = 0u64;
+    let mut i = 0;
+    while i < 4 {
+        let difference = (a[i] as u128).wrapping_sub(b[i] as u128 + borrow as u128);
+        result[i] = difference as u64;
+        borrow = (difference >> 127) as u64;
+        i += 1;
+    }
+    (result, borrow != 0)
+}
+
+const fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    let mut i = 4;
+    while i > 0 {
+        i -= 1;
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+/// Subtracts p once if `value` is not below it. `value` must be below 2p.
+const fn reduce_once(value: [u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    if less_than(&value, p) {
+        value
+    } else {
+        sub_with_borrow(&value, p).0
+    }
+}
+
+/// Computes a * b * R^-1 mod p using coarsely integrated operand scanning.
+fn montgomery_mul(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4], inv: u64) -> [u64; 4] {
+    let mut t = [0u64; 6];
+    for &b_limb in b {
+        // t += a * b_limb
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let product = t[j] as u128 + a[j] as u128 * b_limb as u128 + carry;
+            t[j] = product as u64;
+            carry = product >> 64;
+        }
+        let sum = t[4] as u128 + carry;
+        t[4] = sum as u64;
+        t[5] = (sum >> 64) as u64;
+
+        // t = (t + m * p) / 2^64, where m makes the low limb vanish
+        let m = t[0].wrapping_mul(inv);
+        let mut carry = (t[0] as u128 + m as u128 * p[0] as u128) >> 64;
+        for j in 1..4 {
+            let product = t[j] as u128 + m as u128 * p[j] as u128 + carry;
+            t[j - 1] = product as u64;
+            carry = product >> 64;
+        }
+        let sum = t[4] as u128 + carry;
+        t[3] = sum as u64;
+        t[4] = t[5] + (sum >> 64) as u64;
+        t[5] = 0;
+    }
+
+    // The result is below 2p; p < 2^255 keeps it within four limbs
+    reduce_once([t[0], t[1], t[2], t[3]], p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Bn = Fp256<Bn254Scalar>;
+    type Bls = Fp256<Bls12381Scalar>;
+
+    const A: &str = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+    const B: &str = "0x0fedcba0987654321fedcba0987654321fedcba0987654321fedcba098765432";
+
+    fn hex<P: PrimeModulus256>(s: &str) -> Fp256<P> {
+        Fp256::from_str_radix(s, 16).unwrap()
+    }
+
+    #[test]
+    fn test_derived_constants() {
+        assert_eq!(Bn254Scalar::MODULUS[0].wrapping_mul(Bn254Scalar::INV), u64::MAX);
+        assert_eq!(Bls12381Scalar::INV, 0xffff_fffe_ffff_ffff);
+        assert_eq!(Bn::one().to_canonical(), [1, 0, 0, 0]);
+        assert_eq!(Bn::new(42).to_canonical(), [42, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_bn254_vectors() {
+        let a = hex::<Bn254Scalar>(A);
+        let b = hex::<Bn254Scalar>(B);
+
+        assert_eq!(a * b, hex("0x29b8ff278166b00f53af9cd27019dc749c4cf49131ce82eee321344688bd16ba"));
+        assert_eq!(a + b, hex("0x2222221929222221322222192922222132222219292222213222221929222221"));
+        assert_eq!(a - b, hex("0x2468ad7f83579bcf2468ad7f83579bcf2468ad7f83579bcf2468ad7f83579bd"));
+        assert_eq!(b - a, hex("0x2e1dc39ae8fc266cc609bade894bdea035ed5d708183f6d4519b6abbf7ca8644"));
+        assert_eq!(a.inverse().unwrap(), hex("0x2bb13c11312079eabcc30b313613a9e013db48335cbdb86b9b83ed107fcaea83"));
+    }
+
+    #[test]
+    fn test_bls12_381_vectors() {
+        let a = hex::<Bls12381Scalar>(A);
+        let b = hex::<Bls12381Scalar>(B);
+
+        assert_eq!(a * b, hex("0x591267a627047b9cfa768949b8e66c33a366804918d0f0aa9558f06ab9cf02f2"));
+        assert_eq!(b - a, hex("0x71a71c7b3168038b40f34d30116c5e486177192b07c8e2420db9752707ca8644"));
+        assert_eq!(a.inverse().unwrap(), hex("0x4487cfeb804c96fe4cfd630c84ea20112712808cc7a20f44bba3a9e8e40e5393"));
+    }
+
+    #[test]
+    fn test_two_adic_roots_of_unity() {
+        // The 2^28-th root of unity of BN254, 5^((r-1)/2^28)
+        let root = Bn::from_str_radix(
+            "19103219067921713944291392827692070036145651957329286315305642004821462161904",
+            10,
+        )
+        .unwrap();
+        assert_eq!(root.pow(&[1 << 27, 0, 0, 0]), -Bn::one());
+        assert_eq!(root.pow(&[1 << 28, 0, 0, 0]), Bn::one());
+
+        // The 2^32-th root of unity of BLS12-381, 7^((r-1)/2^32)
+        let root = Bls::from_str_radix(
+            "10238227357739495823651030575849232062558860180284477541189508159991286009131",
+            10,
+        )
+        .unwrap();
+        assert_eq!(root.pow(&[1 << 31, 0, 0, 0]), -Bls::one());
+        assert_eq!(root.pow(&[1 << 32, 0, 0, 0]), Bls::one());
+    }
+
+    #[test]
+    fn test_parsing_and_display() {
+        let minus_one = -Bn::one();
+        assert_eq!(
+            minus_one.to_string(),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616"
+        );
+        assert_eq!(Bn::from_str_radix(&minus_one.to_string(), 10), Some(minus_one));
+        assert_eq!(Bn::zero().to_string(), "0");
+        assert_eq!(Bn::from_canonical(Bn254Scalar::MODULUS), None);
+        assert_eq!(Bn::from_str_radix("0x", 16), None);
+        assert_eq!(Bn::from_str_radix("12g", 16), None);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::constraint::ConstraintKind;
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::{Builder, ConstraintId, Node};
+
+/// A polynomial identity over a fixed number of wires, asserted to equal zero.
+///
+/// Each term is a coefficient times the product of some wires, so the PLONK
+/// gate `q_l*a + q_r*b + q_m*a*b + q_o*c + q_c = 0` over wires `[a, b, c]` has
+/// the terms `(q_l, [0])`, `(q_r, [1])`, `(q_m, [0, 1])`, `(q_o, [2])` and `(q_c, [])`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomGate<V: Value> {
+    name: String,
+    wires: usize,
+    terms: Vec<(V, Vec<usize>)>,
+}
+
+impl<V: Value> CustomGate<V> {
+    /// Creates a gate, checking that every term only uses wires below `wires`.
+    pub(crate) fn new(index: usize, name: &str, wires: usize, terms: Vec<(V, Vec<usize>)>) -> Result<Self, GraphError> {
+        if let Some(&wire) = terms.iter().flat_map(|(_, term)| term).find(|&&wire| wire >= wires) {
+            return Err(GraphError::UnknownWire { gate: index, wire });
+        }
+        Ok(CustomGate {
+            name: name.to_string(),
+            wires,
+            terms,
+        })
+    }
+
+    /// Returns the name the gate was registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of nodes the gate is applied to.
+    pub fn wires(&self) -> usize {
+        self.wires
+    }
+
+    /// Returns the terms: each coefficient and the wires it multiplies.
+    pub fn terms(&self) -> &[(V, Vec<usize>)] {
+        &self.terms
+    }
+
+    /// Returns the degree of the polynomial: the most wires in a single term.
+    pub fn degree(&self) -> usize {
+        self.terms.iter().map(|(_, term)| term.len()).max().unwrap_or(0)
+    }
+
+    /// Evaluates the polynomial with wire `i` set to `inputs[i]`, or returns
+    /// `None` if there is not exactly one input per wire.
+    pub fn evaluate(&self, inputs: &[&V]) -> Option<V> {
+        if inputs.len() != self.wires {
+            return None;
+        }
+        Some(self.terms.iter().fold(V::zero(), |sum, (coefficient, term)| {
+            let product = term.iter().fold(coefficient.clone(), |product, &wire| product.mul(inputs[wire]));
+            sum.add(&product)
+        }))
+    }
+}
+
+/// A handle to a gate registered with `Builder::add_gate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateId {
+    index: usize,
+    // The id of the builder that registered the gate
+    builder: usize,
+}
+
+impl GateId {
+    /// Returns the position of the gate in the builder's registry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Registers a custom gate over `wires` nodes with the given terms.
+    pub fn add_gate(&mut self, name: &str, wires: usize, terms: Vec<(V, Vec<usize>)>) -> Result<GateId, GraphError> {
+        let index = self.gates.len();
+        self.gates.push(CustomGate::new(index, name, wires, terms)?);
+        Ok(GateId {
+            index,
+            builder: self.id,
+        })
+    }
+
+    /// Returns a registered gate.
+    pub fn gate(&self, gate: GateId) -> Result<&CustomGate<V>, GraphError> {
+        if gate.builder != self.id {
+            return Err(GraphError::ForeignGate { gate: gate.index });
+        }
+        self.gates.get(gate.index).ok_or(GraphError::UnknownGate { gate: gate.index })
+    }
+
+    /// Returns this builder's handle for `gate`, which may also come from the
+    /// builder this one was derived from, as `adopt` does for nodes. Gates the
+    /// original registered after the copy was made fail with `GraphError::ForeignGate`.
+    pub fn adopt_gate(&self, gate: GateId) -> Result<GateId, GraphError> {
+        match self.parent {
+            _ if gate.builder == self.id => self.gate(gate).map(|_| gate),
+            Some(parent) if gate.builder == parent.id && gate.index < parent.gates => Ok(GateId {
+                index: gate.index,
+                builder: self.id,
+            }),
+            _ => Err(GraphError::ForeignGate { gate: gate.index }),
+        }
+    }
+
+    /// Asserts that `gate` evaluates to zero with its wires set to `nodes`, in order.
+    ///
+    /// The gate is kept whole rather than expanded into multiplications and
+    /// equalities, so exporters see the identity as declared.
+    pub fn assert_gate(&mut self, gate: GateId, nodes: Vec<Node<V>>) -> Result<ConstraintId, GraphError> {
+        let wires = self.gate(gate)?.wires;
+        if nodes.len() != wires {
+            return Err(GraphError::GateArity {
+                gate: gate.index,
+                expected: wires,
+                found: nodes.len(),
+            });
+        }
+        let nodes = nodes
+            .iter()
+            .map(|node| self.check(node))
+            .collect::<Result<_, _>>()?;
+        Ok(self.push_constraint(
+            ConstraintKind::Gate {
+                gate: gate.index,
+                nodes,
+            },
+            None,
+        ))
+    }
+
+    /// Returns the highest degree of any gate applied by a constraint, or 0 if there are none.
+    pub fn max_gate_degree(&self) -> usize {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint.kind {
+                ConstraintKind::Gate { gate, .. } => Some(self.gates[gate].degree()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{BabyBear, Fp, OverflowMode, Program};
+
+    type F = Fp<BabyBear>;
+
+    #[test]
+    fn test_plonk_gate() {
+        // q_l*a + q_r*b + q_m*a*b + q_o*c + q_c = 0 with selectors for c = a*b + 2a + 3
+        let mut builder = Builder::<F>::new();
+        let gate = builder
+            .add_gate(
+                "plonk",
+                3,
+                vec![
+                    (Fp::new(2), vec![0]),
+                    (Fp::zero(), vec![1]),
+                    (Fp::one(), vec![0, 1]),
+                    (-Fp::one(), vec![2]),
+                    (Fp::new(3), vec![]),
+                ],
+            )
+            .unwrap();
+        assert_eq!(builder.gate(gate).unwrap().degree(), 2);
+        assert_eq!(builder.max_gate_degree(), 0);
+
+        let a = builder.init();
+        let b = builder.init();
+        let (a_id, b_id) = (a.id(), b.id());
+        let c = builder
+            .hint(vec![a.clone(), b.clone()], move |values| {
+                values[a_id] * values[b_id] + Fp::new(2) * values[a_id] + Fp::new(3)
+            })
+            .unwrap();
+        builder.assert_gate(gate, vec![a.clone(), b.clone(), c.clone()]).unwrap();
+        assert_eq!(builder.max_gate_degree(), 2);
+
+        let inputs = HashMap::from([(a.id(), Fp::new(4)), (b.id(), Fp::new(5))]);
+        let mut values = builder.fill_nodes(inputs.clone()).unwrap();
+        assert_eq!(values[&c], Fp::new(31));
+        assert!(builder.check_constraints(&values));
+
+        values.insert(c.id(), Fp::new(30));
+        assert!(!builder.check_constraints(&values));
+        assert_eq!(
+            builder.constraint_violations(&values)[0].to_string(),
+            "Constraint 0 violated: (node 0 = 4, node 1 = 5, node 2 = 30) do not satisfy gate 0"
+        );
+
+        // The gate survives lowering and compilation intact
+        let lowered = builder.lower_constraints().unwrap();
+        assert_eq!(
+            lowered.constraints().collect::<Vec<_>>(),
+            vec![&ConstraintKind::Gate { gate: 0, nodes: vec![0, 1, 2] }]
+        );
+        let program = Program::from_bytes(&builder.compile().unwrap().to_bytes(), &builder).unwrap();
+        let witness = program.run(inputs, OverflowMode::Wrapping).unwrap();
+        assert!(program.check_constraints(&witness));
+        assert!(!program.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_higher_degree() {
+        // x^3 - 8 = 0
+        let mut builder = Builder::<u64>::new();
+        let cube = builder.add_gate("cube", 1, vec![(1, vec![0, 0, 0]), (8u64.wrapping_neg(), vec![])]).unwrap();
+        let x = builder.init();
+        builder.assert_gate(cube, vec![x.clone()]).unwrap();
+        assert_eq!(builder.max_gate_degree(), 3);
+        assert_eq!(builder.gate(cube).unwrap().evaluate(&[&2]), Some(0));
+        assert_eq!(builder.gate(cube).unwrap().evaluate(&[&2, &2]), None);
+        assert_eq!(builder.gate(cube).unwrap().evaluate(&[]), None);
+
+        assert!(builder.check_constraints(&builder.fill_nodes(HashMap::from([(x.id(), 2)])).unwrap()));
+        assert!(!builder.check_constraints(&builder.fill_nodes(HashMap::from([(x.id(), 3)])).unwrap()));
+    }
+
+    #[test]
+    fn test_wires_and_constant_gates() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+
+        assert_eq!(
+            builder.add_gate("bad", 2, vec![(1, vec![0, 2])]).unwrap_err(),
+            GraphError::UnknownWire { gate: 0, wire: 2 }
+        );
+        let square = builder.add_gate("square", 2, vec![(1, vec![0, 0]), (u32::MAX, vec![1])]).unwrap();
+        assert_eq!(
+            builder.assert_gate(square, vec![x.clone()]).unwrap_err(),
+            GraphError::GateArity { gate: 0, expected: 2, found: 1 }
+        );
+
+        // A gate without wires is a constant: zero holds regardless of the witness, anything else never does
+        let empty = builder.add_gate("empty", 0, Vec::new()).unwrap();
+        let one = builder.add_gate("one", 0, vec![(1, Vec::new())]).unwrap();
+        assert_eq!((builder.gate(empty).unwrap().degree(), builder.gate(one).unwrap().degree()), (0, 0));
+        builder.assert_gate(empty, Vec::new()).unwrap();
+        let values = builder.fill_nodes(HashMap::from([(x.id(), 5)])).unwrap();
+        assert!(builder.check_constraints(&values));
+        builder.assert_gate(one, Vec::new()).unwrap();
+        assert!(!builder.check_constraints(&values));
+        assert_eq!(builder.max_gate_degree(), 0);
+    }
+}
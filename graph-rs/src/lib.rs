@@ -1,127 +1,329 @@
 use std::collections::{HashMap};
 use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use diagnostics::Diagnostics;
+
+pub mod batch;
+pub mod cone;
+pub mod constraint;
+pub mod diagnostics;
+pub mod error;
+pub mod field;
+pub mod field256;
+pub mod gate;
+pub mod lookup;
+pub mod observer;
+pub mod parallel;
+pub mod partial;
+pub mod program;
+pub mod range;
+pub mod report;
+pub mod session;
+pub mod streaming;
+pub mod symbolic;
+pub mod value;
+pub mod witness;
+
+pub use constraint::ConstraintKind;
+pub use diagnostics::Verbosity;
+pub use error::GraphError;
+pub use field::{BabyBear, Fp, Goldilocks, Mersenne31, PrimeModulus};
+pub use field256::{Bls12381Scalar, Bn254Scalar, Fp256, PrimeModulus256};
+pub use gate::{CustomGate, GateId};
+pub use lookup::{Table, TableId};
+pub use observer::{NodeEvent, NodeKind, Observer, Printer};
+pub use program::{Encode, Instruction, Program};
+pub use report::Violation;
+pub use session::EvalSession;
+pub use symbolic::Symbolic;
+pub use value::{Field, Value};
+pub use witness::Witness;
+
 /// A node in the computational graph.
-pub struct Node {
+pub struct Node<V: Value> {
     id: usize,
-    node_type: NodeType,
+    // The id of the builder that created this node
+    builder: usize,
+    node_type: NodeType<V>,
 }
 
 /// The type of a node in the computational graph.
-enum NodeType {
+enum NodeType<V: Value> {
     /// An input node that requires a value to be provided.
     Input,
     /// A constant node with a fixed value.
-    Constant(u32),
+    Constant(V),
     /// A node that adds two other nodes.
     Add(usize, usize),
+    /// A node that subtracts the second node from the first.
+    Sub(usize, usize),
     /// A node that multiplies two other nodes.
     Mul(usize, usize),
+    /// A node that negates another node.
+    Neg(usize),
     /// A node whose value is computed outside the graph but constrained within it.
-    Hint(Vec<usize>, Arc<HintFunction>),
+    Hint(Vec<usize>, Arc<HintFunction<V>>),
 }
 
+/// The signature of a hint: the witness computed so far in, hinted value out.
+/// The witness holds at least the hint's dependencies. Built-in hints such as
+/// division can fail with a typed error.
+type HintFn<V> = dyn Fn(&Witness<V>) -> Result<V, GraphError> + Send + Sync;
+
 /// A wrapper for hint functions to enable cloning
-struct HintFunction {
+struct HintFunction<V: Value> {
     // We use a unique ID to identify the function for debug purposes
     id: usize,
     // The actual function is stored in a Box
-    func: Box<dyn Fn(&HashMap<usize, u32>) -> u32 + Send + Sync>,
+    func: Box<HintFn<V>>,
+}
+
+impl<V: Value> HintFunction<V> {
+    /// Runs the hint for `node`, turning a panic into `GraphError::HintFailed`.
+    fn call(&self, node: usize, values: &Witness<V>) -> Result<V, GraphError> {
+        match panic::catch_unwind(AssertUnwindSafe(|| (self.func)(values))) {
+            Ok(result) => result,
+            Err(payload) => {
+                let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                    message.to_string()
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    message.clone()
+                } else {
+                    "hint panicked".to_string()
+                };
+                Err(GraphError::HintFailed {
+                    node,
+                    hint: self.id,
+                    message,
+                })
+            }
+        }
+    }
 }
 
 // Implement Debug for HintFunction
-impl fmt::Debug for HintFunction {
+impl<V: Value> fmt::Debug for HintFunction<V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "HintFunction({})", self.id)
     }
 }
 
-impl Clone for Node {
+impl<V: Value> Clone for Node<V> {
     fn clone(&self) -> Self {
         Node {
             id: self.id,
+            builder: self.builder,
             node_type: self.node_type.clone(),
         }
     }
 }
 
-impl Clone for NodeType {
+impl<V: Value> NodeType<V> {
+    /// Returns the ids of the nodes this node reads.
+    fn operands(&self) -> Vec<usize> {
+        match self {
+            NodeType::Input | NodeType::Constant(_) => Vec::new(),
+            NodeType::Add(a, b) | NodeType::Sub(a, b) | NodeType::Mul(a, b) => vec![*a, *b],
+            NodeType::Neg(a) => vec![*a],
+            NodeType::Hint(deps, _) => deps.clone(),
+        }
+    }
+}
+
+impl<V: Value> Clone for NodeType<V> {
     fn clone(&self) -> Self {
         match self {
             NodeType::Input => NodeType::Input,
-            NodeType::Constant(value) => NodeType::Constant(*value),
+            NodeType::Constant(value) => NodeType::Constant(value.clone()),
             NodeType::Add(a, b) => NodeType::Add(*a, *b),
+            NodeType::Sub(a, b) => NodeType::Sub(*a, *b),
             NodeType::Mul(a, b) => NodeType::Mul(*a, *b),
+            NodeType::Neg(a) => NodeType::Neg(*a),
             NodeType::Hint(deps, func) => NodeType::Hint(deps.clone(), Arc::clone(func)),
         }
     }
 }
 
-impl fmt::Debug for Node {
+impl<V: Value> fmt::Debug for Node<V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.node_type {
             NodeType::Input => write!(f, "Node({}, Input)", self.id),
             NodeType::Constant(value) => write!(f, "Node({}, Constant({}))", self.id, value),
             NodeType::Add(a, b) => write!(f, "Node({}, Add({}, {}))", self.id, a, b),
+            NodeType::Sub(a, b) => write!(f, "Node({}, Sub({}, {}))", self.id, a, b),
             NodeType::Mul(a, b) => write!(f, "Node({}, Mul({}, {}))", self.id, a, b),
+            NodeType::Neg(a) => write!(f, "Node({}, Neg({}))", self.id, a),
             NodeType::Hint(deps, func) => write!(f, "Node({}, Hint({:?}, {:?}))", self.id, deps, func),
         }
     }
 }
 
-impl fmt::Debug for NodeType {
+impl<V: Value> fmt::Debug for NodeType<V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             NodeType::Input => write!(f, "Input"),
             NodeType::Constant(value) => write!(f, "Constant({})", value),
             NodeType::Add(a, b) => write!(f, "Add({}, {})", a, b),
+            NodeType::Sub(a, b) => write!(f, "Sub({}, {})", a, b),
             NodeType::Mul(a, b) => write!(f, "Mul({}, {})", a, b),
+            NodeType::Neg(a) => write!(f, "Neg({})", a),
             NodeType::Hint(deps, func) => write!(f, "Hint({:?}, {:?})", deps, func),
         }
     }
 }
 
-/// A constraint that two nodes must have equal values.
+/// A constraint on the values of some nodes.
 #[derive(Debug, Clone)]
 struct Constraint {
-    left: usize,
-    right: usize,
+    kind: ConstraintKind,
+    // Names the constraint in violation reports
+    label: Option<String>,
+}
+
+/// A handle to a constraint, returned by the `assert_*` methods so it can be labelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintId {
+    index: usize,
+    // The id of the builder that added the constraint
+    builder: usize,
+}
+
+impl<V: Value> Node<V> {
+    /// Returns the id of this node, used to key input and witness values.
+    pub fn id(&self) -> usize {
+        self.id
+    }
 }
 
-impl fmt::Display for Node {
+impl<V: Value> fmt::Display for Node<V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Node({})", self.id)
     }
 }
 
-/// A builder that will be used to create a computational graph.
+/// How arithmetic nodes handle results that do not fit in the value type.
+///
+/// Only bounded integer domains such as `u32` can overflow; field elements
+/// evaluate identically in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    /// Wrap around modulo the size of the type.
+    #[default]
+    Wrapping,
+    /// Fail with `GraphError::Overflow` at the first node that overflows.
+    Checked,
+    /// Clamp to the smallest or largest representable value.
+    Saturating,
+}
+
+impl OverflowMode {
+    /// Adds the operands of `node` under this mode.
+    fn add<V: Value>(self, node: usize, a: &V, b: &V) -> Result<V, GraphError> {
+        match self {
+            OverflowMode::Wrapping => Ok(a.add(b)),
+            OverflowMode::Checked => a.checked_add(b).ok_or_else(|| overflow(node, "Add", &[a, b])),
+            OverflowMode::Saturating => Ok(a.saturating_add(b)),
+        }
+    }
+
+    /// Subtracts the operands of `node` under this mode.
+    fn sub<V: Value>(self, node: usize, a: &V, b: &V) -> Result<V, GraphError> {
+        match self {
+            OverflowMode::Wrapping => Ok(a.sub(b)),
+            OverflowMode::Checked => a.checked_sub(b).ok_or_else(|| overflow(node, "Sub", &[a, b])),
+            OverflowMode::Saturating => Ok(a.saturating_sub(b)),
+        }
+    }
+
+    /// Multiplies the operands of `node` under this mode.
+    fn mul<V: Value>(self, node: usize, a: &V, b: &V) -> Result<V, GraphError> {
+        match self {
+            OverflowMode::Wrapping => Ok(a.mul(b)),
+            OverflowMode::Checked => a.checked_mul(b).ok_or_else(|| overflow(node, "Mul", &[a, b])),
+            OverflowMode::Saturating => Ok(a.saturating_mul(b)),
+        }
+    }
+
+    /// Negates the operand of `node` under this mode.
+    fn neg<V: Value>(self, node: usize, a: &V) -> Result<V, GraphError> {
+        match self {
+            OverflowMode::Wrapping => Ok(a.neg()),
+            OverflowMode::Checked => a.checked_neg().ok_or_else(|| overflow(node, "Neg", &[a])),
+            OverflowMode::Saturating => Ok(a.saturating_neg()),
+        }
+    }
+}
+
+/// Builds the error for an arithmetic node that overflowed.
+fn overflow<V: Value>(node: usize, operation: &'static str, operands: &[&V]) -> GraphError {
+    GraphError::Overflow {
+        node,
+        operation,
+        operands: operands.iter().map(|value| value.to_string()).collect(),
+    }
+}
+
+/// The source of builder ids, so nodes can be traced back to their builder.
+static NEXT_BUILDER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// What a derived builder remembers of the builder it was copied from.
+#[derive(Debug, Clone, Copy)]
+struct Parent {
+    id: usize,
+    // How many nodes, tables and gates the parent had when the copy was made
+    nodes: usize,
+    tables: usize,
+    gates: usize,
+}
+
+/// A builder that will be used to create a computational graph over values of type `V`.
 #[derive(Debug)]
-pub struct Builder {
-    nodes: Vec<Node>,
+pub struct Builder<V: Value> {
+    id: usize,
+    // The builder this one was derived from, if any
+    parent: Option<Parent>,
+    nodes: Vec<Node<V>>,
     constraints: Vec<Constraint>,
+    tables: Vec<Table<V>>,
+    gates: Vec<CustomGate<V>>,
     next_id: usize,
     next_hint_id: usize,
+    diagnostics: Diagnostics,
+}
+
+impl<V: Value> Default for Builder<V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl Builder {
+impl<V: Value> Builder<V> {
     /// Creates a new builder.
     pub fn new() -> Self {
         Builder {
+            id: NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed),
+            parent: None,
             nodes: Vec::new(),
             constraints: Vec::new(),
+            tables: Vec::new(),
+            gates: Vec::new(),
             next_id: 0,
             next_hint_id: 0,
+            diagnostics: Diagnostics::default(),
         }
     }
 
     /// Initializes a node in the graph.
-    pub fn init(&mut self) -> Node {
+    pub fn init(&mut self) -> Node<V> {
         let id = self.next_id;
         self.next_id += 1;
         
         let node = Node {
             id,
+            builder: self.id,
             node_type: NodeType::Input,
         };
         
@@ -130,12 +332,13 @@ impl Builder {
     }
 
     /// Initializes a node in the graph, set to a constant value.
-    pub fn constant(&mut self, value: u32) -> Node {
+    pub fn constant(&mut self, value: V) -> Node<V> {
         let id = self.next_id;
         self.next_id += 1;
         
         let node = Node {
             id,
+            builder: self.id,
             node_type: NodeType::Constant(value),
         };
         
@@ -144,62 +347,206 @@ impl Builder {
     }
 
     /// Adds 2 nodes in the graph, returning a new node.
-    pub fn add(&mut self, a: Node, b: Node) -> Node {
+    pub fn add(&mut self, a: Node<V>, b: Node<V>) -> Result<Node<V>, GraphError> {
+        let (a, b) = (self.check(&a)?, self.check(&b)?);
         let id = self.next_id;
         self.next_id += 1;
         
         let node = Node {
             id,
-            node_type: NodeType::Add(a.id, b.id),
+            builder: self.id,
+            node_type: NodeType::Add(a, b),
         };
         
         self.nodes.push(node.clone());
-        node
+        Ok(node)
+    }
+
+    /// Subtracts node b from node a in the graph, returning a new node.
+    pub fn sub(&mut self, a: Node<V>, b: Node<V>) -> Result<Node<V>, GraphError> {
+        let (a, b) = (self.check(&a)?, self.check(&b)?);
+        let id = self.next_id;
+        self.next_id += 1;
+        
+        let node = Node {
+            id,
+            builder: self.id,
+            node_type: NodeType::Sub(a, b),
+        };
+        
+        self.nodes.push(node.clone());
+        Ok(node)
     }
 
     /// Multiplies 2 nodes in the graph, returning a new node.
-    pub fn mul(&mut self, a: Node, b: Node) -> Node {
+    pub fn mul(&mut self, a: Node<V>, b: Node<V>) -> Result<Node<V>, GraphError> {
+        let (a, b) = (self.check(&a)?, self.check(&b)?);
         let id = self.next_id;
         self.next_id += 1;
         
         let node = Node {
             id,
-            node_type: NodeType::Mul(a.id, b.id),
+            builder: self.id,
+            node_type: NodeType::Mul(a, b),
         };
         
         self.nodes.push(node.clone());
-        node
+        Ok(node)
+    }
+
+    /// Negates a node in the graph, returning a new node.
+    pub fn neg(&mut self, a: Node<V>) -> Result<Node<V>, GraphError> {
+        let a = self.check(&a)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        
+        let node = Node {
+            id,
+            builder: self.id,
+            node_type: NodeType::Neg(a),
+        };
+        
+        self.nodes.push(node.clone());
+        Ok(node)
     }
 
     /// Asserts that 2 nodes are equal.
-    pub fn assert_equal(&mut self, a: Node, b: Node) {
-        self.constraints.push(Constraint {
-            left: a.id,
-            right: b.id,
-        });
+    pub fn assert_equal(&mut self, a: Node<V>, b: Node<V>) -> Result<ConstraintId, GraphError> {
+        let (a, b) = (self.check(&a)?, self.check(&b)?);
+        Ok(self.push_constraint(ConstraintKind::Equal(a, b), None))
     }
 
     /// An API for hinting values that allows you to perform operations
-    /// like division or computing square roots.
-    pub fn hint<F>(&mut self, dependencies: Vec<Node>, compute_func: F) -> Node
+    /// like division or computing square roots. The function reads the
+    /// values of its dependencies from the witness by node id.
+    ///
+    /// If the function panics, evaluation fails with `GraphError::HintFailed`
+    /// instead of unwinding through the caller.
+    pub fn hint<F>(&mut self, dependencies: Vec<Node<V>>, compute_func: F) -> Result<Node<V>, GraphError>
     where
-        F: Fn(&HashMap<usize, u32>) -> u32 + Send + Sync + 'static,
+        F: Fn(&Witness<V>) -> V + Send + Sync + 'static,
     {
+        let dependency_ids = dependencies
+            .iter()
+            .map(|node| self.check(node))
+            .collect::<Result<_, _>>()?;
+        Ok(self.push_hint(dependency_ids, Box::new(move |values| Ok(compute_func(values)))))
+    }
+
+    /// Adds a hint whose function can fail. An `Err` from the function fails
+    /// evaluation with `GraphError::HintFailed`, carrying the error's message.
+    pub fn try_hint<F, E>(&mut self, dependencies: Vec<Node<V>>, compute_func: F) -> Result<Node<V>, GraphError>
+    where
+        F: Fn(&Witness<V>) -> Result<V, E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        let dependency_ids = dependencies
+            .iter()
+            .map(|node| self.check(node))
+            .collect::<Result<_, _>>()?;
+        let (node, hint) = (self.next_id, self.next_hint_id);
+        Ok(self.push_hint(
+            dependency_ids,
+            Box::new(move |values| {
+                compute_func(values).map_err(|error| GraphError::HintFailed {
+                    node,
+                    hint,
+                    message: error.to_string(),
+                })
+            }),
+        ))
+    }
+
+    /// Returns the id of a node, checking that it was created by this builder.
+    fn check(&self, node: &Node<V>) -> Result<usize, GraphError> {
+        if node.builder != self.id {
+            return Err(GraphError::ForeignNode { node: node.id });
+        }
+        if node.id >= self.next_id {
+            return Err(GraphError::UnknownNode { node: node.id });
+        }
+        Ok(node.id)
+    }
+
+    /// Returns this builder's handle for `node`, which may also come from the
+    /// builder this one was derived from by `partial_eval` or `lower_constraints`.
+    ///
+    /// A derived builder has its own identity, since both builders keep adding
+    /// nodes independently and their new ids collide, so handles from the
+    /// original are rejected until adopted. Nodes the original added after the
+    /// copy was made fail with `GraphError::ForeignNode`. Tables and gates are
+    /// adopted the same way, with `adopt_table` and `adopt_gate`.
+    pub fn adopt(&self, node: &Node<V>) -> Result<Node<V>, GraphError> {
+        match self.parent {
+            _ if node.builder == self.id => self.handle(self.check(node)?),
+            Some(parent) if node.builder == parent.id && node.id < parent.nodes => self.handle(node.id),
+            _ => Err(GraphError::ForeignNode { node: node.id }),
+        }
+    }
+
+    /// Returns a copy of the graph under a fresh identity, with this builder as its parent.
+    fn derive(&self) -> Builder<V> {
+        let id = NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed);
+        Builder {
+            id,
+            parent: Some(Parent {
+                id: self.id,
+                nodes: self.next_id,
+                tables: self.tables.len(),
+                gates: self.gates.len(),
+            }),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| Node {
+                    id: node.id,
+                    builder: id,
+                    node_type: node.node_type.clone(),
+                })
+                .collect(),
+            constraints: self.constraints.clone(),
+            tables: self.tables.clone(),
+            gates: self.gates.clone(),
+            next_id: self.next_id,
+            next_hint_id: self.next_hint_id,
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
+
+    /// Returns a handle to an existing node of this builder.
+    fn handle(&self, id: usize) -> Result<Node<V>, GraphError> {
+        self.nodes
+            .iter()
+            .find(|node| node.id == id)
+            .cloned()
+            .ok_or(GraphError::UnknownNode { node: id })
+    }
+
+    /// Adds a constraint with an optional label.
+    fn push_constraint(&mut self, kind: ConstraintKind, label: Option<String>) -> ConstraintId {
+        self.constraints.push(Constraint { kind, label });
+        ConstraintId {
+            index: self.constraints.len() - 1,
+            builder: self.id,
+        }
+    }
+
+    /// Adds a hint node backed by a possibly failing function.
+    fn push_hint(&mut self, dependency_ids: Vec<usize>, func: Box<HintFn<V>>) -> Node<V> {
         let id = self.next_id;
         self.next_id += 1;
         
         let hint_id = self.next_hint_id;
         self.next_hint_id += 1;
         
-        let dependency_ids = dependencies.iter().map(|node| node.id).collect();
-        
         let hint_function = HintFunction {
             id: hint_id,
-            func: Box::new(compute_func),
+            func,
         };
         
         let node = Node {
             id,
+            builder: self.id,
             node_type: NodeType::Hint(dependency_ids, Arc::new(hint_function)),
         };
         
@@ -208,97 +555,201 @@ impl Builder {
     }
 
     /// Fills in all the nodes of the graph based on setting the values of the "input nodes".
-    pub fn fill_nodes(&self, inputs: HashMap<usize, u32>) -> Result<HashMap<usize, u32>, String> {
-        // Debug print the nodes
+    ///
+    /// Arithmetic wraps on overflow; see `fill_nodes_with_mode` for the alternatives.
+    pub fn fill_nodes(&self, inputs: HashMap<usize, V>) -> Result<Witness<V>, GraphError> {
+        self.fill_nodes_with_mode(inputs, OverflowMode::Wrapping)
+    }
+
+    /// Fills in all the nodes of the graph, handling arithmetic overflow according to `mode`.
+    ///
+    /// Reports the graph and any error through the builder's diagnostics; see
+    /// `set_verbosity`. `fill_nodes_observed` reports each evaluated node instead.
+    pub fn fill_nodes_with_mode(
+        &self,
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+    ) -> Result<Witness<V>, GraphError> {
+        // Trace the nodes
         for (i, node) in self.nodes.iter().enumerate() {
-            println!("Node {}: {:?}", i, node);
+            self.diagnostics.emit(Verbosity::Trace, || format!("Node {}: {:?}", i, node));
         }
         
-        // Validate that all input nodes have values
+        let report = |error: GraphError| {
+            self.diagnostics.emit(Verbosity::Errors, || error.to_string());
+            error
+        };
+        
+        // Create a witness to store computed values, starting from the inputs
+        let mut values = self.input_witness(inputs).map_err(report)?;
+        
+        // Process nodes so that every operand is computed before its users
+        for index in self.topological_order().map_err(report)? {
+            let node = &self.nodes[index];
+            if let NodeType::Input = node.node_type {
+                // Already handled above
+                continue;
+            }
+            
+            match self.eval_node(node, &values, mode) {
+                Ok(value) => {
+                    values.insert(node.id, value);
+                }
+                Err(error) => {
+                    let error = report(error);
+                    self.diagnostics.emit(Verbosity::Errors, || format!("Values map: {:?}", values));
+                    return Err(error);
+                }
+            }
+        }
+        
+        Ok(values)
+    }
+
+    /// Validates that all input nodes have values and seeds a witness with them.
+    /// Values for ids that are not part of the graph are rejected.
+    fn input_witness(&self, inputs: HashMap<usize, V>) -> Result<Witness<V>, GraphError> {
+        self.seed_witness(inputs, Witness::with_len(self.next_id))
+    }
+
+    /// Validates the inputs as `input_witness` does, inserting them into `values`.
+    fn seed_witness(&self, inputs: HashMap<usize, V>, mut values: Witness<V>) -> Result<Witness<V>, GraphError> {
         for node in &self.nodes {
             if let NodeType::Input = node.node_type {
                 if !inputs.contains_key(&node.id) {
-                    return Err(format!("Missing value for input node {}", node.id));
+                    return Err(GraphError::MissingInput { node: node.id });
                 }
             }
         }
         
-        // Create a map to store computed values
-        let mut values = HashMap::new();
-        
-        // Add input values to the map
         for (id, value) in inputs {
+            if id >= self.next_id {
+                return Err(GraphError::UnknownNode { node: id });
+            }
             values.insert(id, value);
         }
+        Ok(values)
+    }
+
+    /// Returns, for each node, the indices into `self.nodes` of its operands.
+    /// Operands that are not part of the graph are skipped.
+    fn operand_indices(&self) -> Vec<Vec<usize>> {
+        let index_of: HashMap<usize, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+        self.nodes
+            .iter()
+            .map(|node| {
+                node.node_type
+                    .operands()
+                    .iter()
+                    .filter_map(|id| index_of.get(id).copied())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Orders the nodes so that every node comes after its operands.
+    ///
+    /// Returns indices into `self.nodes`. Graphs built in insertion order keep
+    /// that order. Operands that are not part of the graph are left for
+    /// evaluation to report.
+    fn topological_order(&self) -> Result<Vec<usize>, GraphError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            OnStack,
+            Done,
+        }
         
-        // Process nodes in order
-        for node in &self.nodes {
-            match &node.node_type {
-                NodeType::Input => {
-                    // Already handled above
-                }
-                NodeType::Constant(value) => {
-                    values.insert(node.id, *value);
-                }
-                NodeType::Add(a, b) => {
-                    if let (Some(a_value), Some(b_value)) = (values.get(a), values.get(b)) {
-                        values.insert(node.id, a_value.wrapping_add(*b_value));
-                    } else {
-                        println!("Missing values for Add operation at node {}. a={}, b={}", node.id, a, b);
-                        println!("Values map: {:?}", values);
-                        return Err(format!("Missing values for Add operation at node {}", node.id));
-                    }
-                }
-                NodeType::Mul(a, b) => {
-                    if let (Some(a_value), Some(b_value)) = (values.get(a), values.get(b)) {
-                        values.insert(node.id, a_value.wrapping_mul(*b_value));
-                    } else {
-                        println!("Missing values for Mul operation at node {}. a={}, b={}", node.id, a, b);
-                        println!("Values map: {:?}", values);
-                        return Err(format!("Missing values for Mul operation at node {}", node.id));
-                    }
-                }
-                NodeType::Hint(deps, func) => {
-                    // Create a map of dependency values
-                    let mut dep_values = HashMap::new();
-                    let mut missing_deps = false;
-                    
-                    for &dep_id in deps {
-                        if let Some(value) = values.get(&dep_id) {
-                            dep_values.insert(dep_id, *value);
-                        } else {
-                            println!("Missing dependency value {} for Hint at node {}", dep_id, node.id);
-                            missing_deps = true;
+        let operands = self.operand_indices();
+        let mut marks = vec![Mark::Unvisited; self.nodes.len()];
+        let mut order = Vec::with_capacity(self.nodes.len());
+        
+        // Iterative depth-first search; each stack entry is a node and the next operand to visit
+        for root in 0..self.nodes.len() {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+            
+            let mut stack = vec![(root, 0)];
+            marks[root] = Mark::OnStack;
+            while let Some((index, next)) = stack.last_mut() {
+                let index = *index;
+                if let Some(&operand) = operands[index].get(*next) {
+                    *next += 1;
+                    match marks[operand] {
+                        Mark::Unvisited => {
+                            marks[operand] = Mark::OnStack;
+                            stack.push((operand, 0));
                         }
+                        Mark::OnStack => {
+                            // The stack from `operand` upwards is the cycle
+                            let start = stack.iter().position(|&(i, _)| i == operand).unwrap();
+                            let nodes = stack[start..].iter().map(|&(i, _)| self.nodes[i].id).collect();
+                            return Err(GraphError::Cycle { nodes });
+                        }
+                        Mark::Done => {}
                     }
-                    
-                    if missing_deps {
-                        return Err(format!("Missing dependency values for Hint at node {}", node.id));
-                    }
-                    
-                    // Compute the hint value
-                    let hint_value = (func.func)(&dep_values);
-                    values.insert(node.id, hint_value);
+                } else {
+                    marks[index] = Mark::Done;
+                    order.push(index);
+                    stack.pop();
                 }
             }
         }
         
-        Ok(values)
+        Ok(order)
+    }
+
+    /// Computes the value of a single node from the values of its operands.
+    fn eval_node(
+        &self,
+        node: &Node<V>,
+        values: &Witness<V>,
+        mode: OverflowMode,
+    ) -> Result<V, GraphError> {
+        let operand = |id: &usize| {
+            if *id >= self.next_id {
+                return Err(GraphError::UnknownNode { node: *id });
+            }
+            values.get(*id).ok_or(GraphError::MissingOperand {
+                node: node.id,
+                operand: *id,
+            })
+        };
+        
+        match &node.node_type {
+            NodeType::Input => values
+                .get(node.id)
+                .cloned()
+                .ok_or(GraphError::MissingInput { node: node.id }),
+            NodeType::Constant(value) => Ok(value.clone()),
+            NodeType::Add(a, b) => mode.add(node.id, operand(a)?, operand(b)?),
+            NodeType::Sub(a, b) => mode.sub(node.id, operand(a)?, operand(b)?),
+            NodeType::Mul(a, b) => mode.mul(node.id, operand(a)?, operand(b)?),
+            NodeType::Neg(a) => mode.neg(node.id, operand(a)?),
+            NodeType::Hint(deps, func) => {
+                // Make sure every dependency has a value before handing over the witness
+                for dep_id in deps {
+                    operand(dep_id)?;
+                }
+                
+                // Compute the hint value
+                func.call(node.id, values)
+            }
+        }
     }
 
     /// Checks that all the constraints hold.
-    pub fn check_constraints(&self, values: &HashMap<usize, u32>) -> bool {
+    pub fn check_constraints(&self, values: &Witness<V>) -> bool {
         for constraint in &self.constraints {
-            let left_value = values.get(&constraint.left);
-            let right_value = values.get(&constraint.right);
-            
-            match (left_value, right_value) {
-                (Some(left), Some(right)) => {
-                    if left != right {
-                        return false;
-                    }
-                }
-                _ => return false, // Missing values for constrained nodes
+            // Missing values for constrained nodes fail too
+            if !constraint.kind.holds(values, &self.tables, &self.gates) {
+                return false;
             }
         }
         
@@ -306,6 +757,51 @@ impl Builder {
     }
 }
 
+impl<V: Field> Builder<V> {
+    /// Divides node a by node b, returning the quotient as a new node.
+    ///
+    /// The quotient is hinted and constrained by `quotient * b == a`, and b is
+    /// constrained to be non-zero through `inv`. Evaluating with b = 0 fails
+    /// with `GraphError::DivisionByZero`.
+    pub fn div(&mut self, a: Node<V>, b: Node<V>) -> Result<Node<V>, GraphError> {
+        let a_id = self.check(&a)?;
+        let b_inverse = self.inv(b.clone())?;
+        
+        let b_inverse_id = b_inverse.id;
+        let quotient = self.push_hint(
+            vec![a_id, b_inverse_id],
+            Box::new(move |values| Ok(values[a_id].mul(&values[b_inverse_id]))),
+        );
+        
+        let product = self.mul(quotient.clone(), b)?;
+        self.assert_equal(product, a)?;
+        Ok(quotient)
+    }
+
+    /// Inverts a node, returning the inverse as a new node.
+    ///
+    /// The inverse is hinted and constrained by `a * inverse == 1`, which also
+    /// proves a is non-zero. Evaluating with a = 0 fails with
+    /// `GraphError::DivisionByZero`.
+    pub fn inv(&mut self, a: Node<V>) -> Result<Node<V>, GraphError> {
+        let a_id = self.check(&a)?;
+        let node = self.next_id;
+        let inverse = self.push_hint(
+            vec![a_id],
+            Box::new(move |values| {
+                values[a_id]
+                    .inverse()
+                    .ok_or(GraphError::DivisionByZero { node, divisor: a_id })
+            }),
+        );
+        
+        let one = self.constant(V::one());
+        let product = self.mul(a, inverse.clone())?;
+        self.assert_equal(product, one)?;
+        Ok(inverse)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,115 +809,543 @@ mod tests {
     #[test]
     fn test_example_1() {
         // Example 1: f(x) = x^2 + x + 5
-        let mut builder = Builder::new();
+        let mut builder = Builder::<Fp<Goldilocks>>::new();
         
         // Create nodes
         let x = builder.init(); // id: 0
         println!("x: {:?}", x);
         
-        let x_squared = builder.mul(x.clone(), x.clone()); // id: 1
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap(); // id: 1
         println!("x_squared: {:?}", x_squared);
         
-        let five = builder.constant(5); // id: 2
+        let five = builder.constant(Fp::new(5)); // id: 2
         println!("five: {:?}", five);
         
-        let x_squared_plus_x = builder.add(x_squared, x); // id: 3
+        let x_squared_plus_x = builder.add(x_squared, x).unwrap(); // id: 3
         println!("x_squared_plus_x: {:?}", x_squared_plus_x);
         
-        let _result = builder.add(x_squared_plus_x, five); // id: 4
+        let _result = builder.add(x_squared_plus_x, five).unwrap(); // id: 4
         println!("result: {:?}", _result);
         
         // Test with x = 3
         let mut inputs = HashMap::new();
-        inputs.insert(0, 3); // x = 3
+        inputs.insert(0, Fp::new(3)); // x = 3
         
         let values = builder.fill_nodes(inputs).unwrap();
         println!("Final values: {:?}", values);
         
-        assert_eq!(values.get(&4), Some(&17)); // y = 3^2 + 3 + 5 = 9 + 3 + 5 = 17
+        assert_eq!(values.get(4), Some(&Fp::new(17))); // y = 3^2 + 3 + 5 = 9 + 3 + 5 = 17
         assert!(builder.check_constraints(&values));
     }
 
     #[test]
     fn test_example_2() {
         // Example 2: f(a) = (a+1) / 8
-        let mut builder = Builder::new();
+        let mut builder = Builder::<Fp<Goldilocks>>::new();
         
         // Create nodes
         let a = builder.init(); // id: 0
         println!("a: {:?}", a);
         
-        let one = builder.constant(1); // id: 1
+        let one = builder.constant(Fp::new(1)); // id: 1
         println!("one: {:?}", one);
         
-        let b = builder.add(a.clone(), one); // id: 2
+        let b = builder.add(a.clone(), one).unwrap(); // id: 2
         println!("b: {:?}", b);
         
-        let eight = builder.constant(8); // id: 3
+        let eight = builder.constant(Fp::new(8)); // id: 3
         println!("eight: {:?}", eight);
         
         // Hint for division: c = b / 8
         let c = builder.hint(vec![b.clone()], |values| {
             println!("Hint values: {:?}", values);
-            let b_value = *values.get(&2).unwrap_or(&0);
-            b_value / 8
-        }); // id: 4
+            let b_value = *values.get(2).unwrap_or(&Fp::zero());
+            b_value * Fp::new(8).inverse().unwrap()
+        }).unwrap(); // id: 4
         println!("c: {:?}", c);
         
         // Constraint: c * 8 = b
-        let c_times_8 = builder.mul(c.clone(), eight); // id: 5
+        let c_times_8 = builder.mul(c.clone(), eight).unwrap(); // id: 5
         println!("c_times_8: {:?}", c_times_8);
         
-        builder.assert_equal(c_times_8, b);
+        builder.assert_equal(c_times_8, b).unwrap();
         
         // Test with a = 15
         let mut inputs = HashMap::new();
-        inputs.insert(0, 15); // a = 15
+        inputs.insert(0, Fp::new(15)); // a = 15
         
         let values = builder.fill_nodes(inputs).unwrap();
         println!("Final values: {:?}", values);
         
-        assert_eq!(values.get(&4), Some(&2)); // c = (15+1)/8 = 16/8 = 2
+        assert_eq!(values.get(4), Some(&Fp::new(2))); // c = (15+1)/8 = 16/8 = 2
         assert!(builder.check_constraints(&values));
     }
 
     #[test]
     fn test_example_3() {
         // Example 3: f(x) = sqrt(x+7)
-        let mut builder = Builder::new();
+        let mut builder = Builder::<Fp<Goldilocks>>::new();
         
         // Create nodes
         let x = builder.init(); // id: 0
         println!("x: {:?}", x);
         
-        let seven = builder.constant(7); // id: 1
+        let seven = builder.constant(Fp::new(7)); // id: 1
         println!("seven: {:?}", seven);
         
-        let x_plus_seven = builder.add(x, seven); // id: 2
+        let x_plus_seven = builder.add(x, seven).unwrap(); // id: 2
         println!("x_plus_seven: {:?}", x_plus_seven);
         
         // Hint for square root
         let sqrt_x_plus_7 = builder.hint(vec![x_plus_seven.clone()], |values| {
             println!("Hint values: {:?}", values);
-            let x_plus_seven_value = *values.get(&2).unwrap_or(&0);
-            (x_plus_seven_value as f64).sqrt() as u32
-        }); // id: 3
+            let x_plus_seven_value = values.get(2).unwrap_or(&Fp::zero()).value();
+            Fp::new((x_plus_seven_value as f64).sqrt() as u64)
+        }).unwrap(); // id: 3
         println!("sqrt_x_plus_7: {:?}", sqrt_x_plus_7);
         
         // Constraint: sqrt_x_plus_7 * sqrt_x_plus_7 = x_plus_seven
-        let computed_sq = builder.mul(sqrt_x_plus_7.clone(), sqrt_x_plus_7.clone()); // id: 4
+        let computed_sq = builder.mul(sqrt_x_plus_7.clone(), sqrt_x_plus_7.clone()).unwrap(); // id: 4
         println!("computed_sq: {:?}", computed_sq);
         
-        builder.assert_equal(computed_sq, x_plus_seven);
+        builder.assert_equal(computed_sq, x_plus_seven).unwrap();
         
         // Test with x = 2 (so x+7 = 9, sqrt = 3)
         let mut inputs = HashMap::new();
-        inputs.insert(0, 2);
+        inputs.insert(0, Fp::new(2));
         
         let values = builder.fill_nodes(inputs).unwrap();
         println!("Final values: {:?}", values);
         
-        assert_eq!(values.get(&3), Some(&3)); // sqrt(2+7) = sqrt(9) = 3
+        assert_eq!(values.get(3), Some(&Fp::new(3))); // sqrt(2+7) = sqrt(9) = 3
+        assert!(builder.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_inexact_division_is_sound() {
+        // (a+1) / 8 for a = 16: there is no integer quotient, but the field
+        // quotient is the unique c with c * 8 == a + 1.
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let a = builder.init();
+        let one = builder.constant(Fp::one());
+        let b = builder.add(a, one).unwrap();
+        let eight = builder.constant(Fp::new(8));
+        let c = builder.hint(vec![b.clone()], |values| {
+            values[2] * Fp::new(8).inverse().unwrap()
+        }).unwrap();
+        let c_times_8 = builder.mul(c.clone(), eight).unwrap();
+        builder.assert_equal(c_times_8, b).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(0, Fp::new(16));
+        let mut values = builder.fill_nodes(inputs).unwrap();
+        assert!(builder.check_constraints(&values));
+        assert_eq!(values[4] * Fp::new(8), Fp::new(17));
+
+        // Any other candidate quotient breaks the constraint.
+        values.insert(5, (values[4] + Fp::one()) * Fp::new(8));
+        values.insert(4, values[4] + Fp::one());
+        assert!(!builder.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_u32_wraps() {
+        // The original u32 behaviour: arithmetic modulo 2^32
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let big = builder.constant(u32::MAX);
+        let sum = builder.add(x.clone(), big).unwrap();
+        let _product = builder.mul(sum, x).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(0, 3);
+        let values = builder.fill_nodes(inputs).unwrap();
+
+        assert_eq!(values.get(2), Some(&2));
+        assert_eq!(values.get(3), Some(&6));
+    }
+
+    #[test]
+    fn test_f64_numeric() {
+        // sqrt(x + 7) evaluated numerically
+        let mut builder = Builder::<f64>::new();
+        let x = builder.init();
+        let seven = builder.constant(7.0);
+        let x_plus_seven = builder.add(x, seven).unwrap();
+        let root = builder.hint(vec![x_plus_seven.clone()], |values| values[2].sqrt()).unwrap();
+        let squared = builder.mul(root.clone(), root).unwrap();
+        builder.assert_equal(squared, x_plus_seven).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(0, 9.0);
+        let values = builder.fill_nodes(inputs).unwrap();
+
+        assert_eq!(values.get(3), Some(&4.0));
         assert!(builder.check_constraints(&values));
     }
+
+    #[test]
+    fn test_symbolic() {
+        let mut builder = Builder::<Symbolic>::new();
+        let x = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let five = builder.constant(Symbolic::from_u64(5));
+        let x_squared_plus_x = builder.add(x_squared, x.clone()).unwrap();
+        let result = builder.add(x_squared_plus_x, five).unwrap();
+        let root = builder.hint(vec![result.clone()], |values| {
+            Symbolic::apply("sqrt", vec![values[4].clone()])
+        }).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(x.id, Symbolic::var("x"));
+        let values = builder.fill_nodes(inputs).unwrap();
+
+        assert_eq!(values[&result].to_string(), "(((x * x) + x) + 5)");
+        assert_eq!(values[&root].to_string(), "sqrt((((x * x) + x) + 5))");
+    }
+
+    #[test]
+    fn test_256_bit_fields() {
+        fn example_2<P: PrimeModulus256>() {
+            // f(a) = (a+1) / 8, with a chosen so a+1 is not a multiple of 8
+            let mut builder = Builder::<Fp256<P>>::new();
+            let a = builder.init();
+            let one = builder.constant(Fp256::one());
+            let b = builder.add(a.clone(), one).unwrap();
+            let eight = builder.constant(Fp256::new(8));
+            let c = builder.hint(vec![b.clone()], |values| {
+                values[2] * Fp256::new(8).inverse().unwrap()
+            }).unwrap();
+            let c_times_8 = builder.mul(c.clone(), eight).unwrap();
+            builder.assert_equal(c_times_8, b).unwrap();
+
+            let mut inputs = HashMap::new();
+            inputs.insert(a.id, -Fp256::new(3));
+            let values = builder.fill_nodes(inputs).unwrap();
+
+            // (-3 + 1) / 8 = -1/4
+            assert_eq!(values[&c] * Fp256::new(4), -Fp256::one());
+            assert!(builder.check_constraints(&values));
+        }
+
+        example_2::<Bn254Scalar>();
+        example_2::<Bls12381Scalar>();
+    }
+
+    #[test]
+    fn test_sub_and_neg() {
+        // f(a, b) = (a - b) * -b
+        let mut builder = Builder::<Fp<Goldilocks>>::new();
+        let a = builder.init();
+        let b = builder.init();
+        let difference = builder.sub(a.clone(), b.clone()).unwrap();
+        let minus_b = builder.neg(b.clone()).unwrap();
+        let result = builder.mul(difference.clone(), minus_b.clone()).unwrap();
+
+        assert_eq!(format!("{:?}", difference), "Node(2, Sub(0, 1))");
+        assert_eq!(format!("{:?}", minus_b), "Node(3, Neg(1))");
+
+        let mut inputs = HashMap::new();
+        inputs.insert(a.id, Fp::new(2));
+        inputs.insert(b.id, Fp::new(5));
+        let values = builder.fill_nodes(inputs).unwrap();
+
+        assert_eq!(values[&difference], -Fp::new(3));
+        assert_eq!(values[&minus_b], -Fp::new(5));
+        assert_eq!(values[&result], Fp::new(15));
+    }
+
+    #[test]
+    fn test_div_and_inv() {
+        // f(x, y) = (x * y) + (x / y)
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_times_y = builder.mul(x.clone(), y.clone()).unwrap();
+        let x_div_y = builder.div(x.clone(), y.clone()).unwrap();
+        let result = builder.add(x_times_y, x_div_y.clone()).unwrap();
+        let y_inverse = builder.inv(y.clone()).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(x.id, Fp::new(10));
+        inputs.insert(y.id, Fp::new(2));
+        let values = builder.fill_nodes(inputs).unwrap();
+
+        assert_eq!(values[&x_div_y], Fp::new(5));
+        assert_eq!(values[&result], Fp::new(25));
+        assert_eq!(values[&y_inverse] * Fp::new(2), Fp::one());
+        assert!(builder.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let _quotient = builder.div(x.clone(), y.clone()).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(x.id, Fp::new(10));
+        inputs.insert(y.id, Fp::zero());
+
+        // The divisor's inverse is the first node div emits
+        let error = builder.fill_nodes(inputs).unwrap_err();
+        assert_eq!(error, GraphError::DivisionByZero { node: 2, divisor: y.id });
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let _sum = builder.add(x.clone(), y).unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(x.id, 1);
+        assert_eq!(builder.fill_nodes(inputs).unwrap_err(), GraphError::MissingInput { node: 1 });
+    }
+
+    #[test]
+    fn test_overflow_modes() {
+        // f(x) = (x * x) + 1 - 3
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let one = builder.constant(1);
+        let sum = builder.add(x_squared.clone(), one.clone()).unwrap();
+        let three = builder.constant(3);
+        let result = builder.sub(sum.clone(), three).unwrap();
+
+        let inputs = |x_value: u32| HashMap::from([(x.id, x_value)]);
+
+        let values = builder.fill_nodes_with_mode(inputs(1 << 16), OverflowMode::Wrapping).unwrap();
+        assert_eq!(values[&result], u32::MAX - 1);
+
+        let values = builder.fill_nodes_with_mode(inputs(1 << 16), OverflowMode::Saturating).unwrap();
+        assert_eq!(values[&x_squared], u32::MAX);
+        assert_eq!(values[&result], u32::MAX - 3);
+
+        let error = builder.fill_nodes_with_mode(inputs(1 << 16), OverflowMode::Checked).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::Overflow {
+                node: x_squared.id,
+                operation: "Mul",
+                operands: vec!["65536".to_string(), "65536".to_string()],
+            }
+        );
+
+        // 1 * 1 + 1 - 3 underflows at the subtraction
+        let error = builder.fill_nodes_with_mode(inputs(1), OverflowMode::Checked).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::Overflow {
+                node: result.id,
+                operation: "Sub",
+                operands: vec!["2".to_string(), "3".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_fields_never_overflow() {
+        let mut builder = Builder::<Fp<Mersenne31>>::new();
+        let x = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let minus_x_squared = builder.neg(x_squared).unwrap();
+
+        let inputs = HashMap::from([(x.id, Fp::new(1 << 20))]);
+        let wrapping = builder.fill_nodes(inputs.clone()).unwrap();
+        let checked = builder.fill_nodes_with_mode(inputs, OverflowMode::Checked).unwrap();
+        assert_eq!(wrapping, checked);
+        assert_eq!(checked[&minus_x_squared], -Fp::new(1 << 9));
+    }
+
+    #[test]
+    fn test_operands_created_later() {
+        // f(x) = x^2 + x + 5, with the nodes stored in reverse, as after a merge
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let five = builder.constant(5);
+        let x_squared_plus_x = builder.add(x_squared, x.clone()).unwrap();
+        let result = builder.add(x_squared_plus_x, five).unwrap();
+        builder.nodes.reverse();
+
+        let values = builder.fill_nodes(HashMap::from([(x.id, 3)])).unwrap();
+        assert_eq!(values[&result], 17);
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let a = builder.add(x.clone(), x.clone()).unwrap();
+        let b = builder.mul(a.clone(), x.clone()).unwrap();
+        let _c = builder.neg(b.clone()).unwrap();
+
+        // Rewire a = b + x, closing the cycle a -> b -> a
+        builder.nodes[a.id].node_type = NodeType::Add(b.id, x.id);
+
+        let error = builder.fill_nodes(HashMap::from([(x.id, 3)])).unwrap_err();
+        assert_eq!(error, GraphError::Cycle { nodes: vec![a.id, b.id] });
+    }
+
+    #[test]
+    fn test_foreign_nodes() {
+        let mut first = Builder::<u32>::new();
+        let mut second = Builder::<u32>::new();
+        let x = first.init();
+        let y = second.init();
+        let z = second.init();
+
+        assert_eq!(second.add(x.clone(), y.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id });
+        assert_eq!(second.mul(y.clone(), x.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id });
+        assert_eq!(second.neg(x.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id });
+        assert_eq!(second.assert_equal(y.clone(), x.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id });
+        assert_eq!(
+            second.hint(vec![y.clone(), x.clone()], |values| values[0]).unwrap_err(),
+            GraphError::ForeignNode { node: x.id }
+        );
+
+        // Rejected calls leave the graph untouched
+        assert_eq!(second.nodes.len(), 2);
+        assert!(second.constraints.is_empty());
+        let sum = second.add(y, z).unwrap();
+        assert_eq!(sum.id, 2);
+
+        // A partially evaluated copy has its own identity, so once both grow
+        // the colliding ids cannot be confused
+        let mut specialised = first.partial_eval(HashMap::new(), OverflowMode::Wrapping).unwrap();
+        let negated = specialised.neg(specialised.adopt(&x).unwrap()).unwrap();
+        let later = first.add(x.clone(), x.clone()).unwrap();
+        assert_eq!((negated.id, later.id), (1, 1));
+        assert_eq!(specialised.neg(x.clone()).unwrap_err(), GraphError::ForeignNode { node: x.id });
+        assert_eq!(specialised.adopt(&later).unwrap_err(), GraphError::ForeignNode { node: 1 });
+        assert_eq!(first.adopt(&negated).unwrap_err(), GraphError::ForeignNode { node: 1 });
+        assert_eq!(specialised.adopt(&negated).unwrap().id, negated.id);
+    }
+
+    #[test]
+    fn test_foreign_handles() {
+        // Both builders have a table, gate and constraint at index 0
+        let mut first = Builder::<u32>::new();
+        let mut second = Builder::<u32>::new();
+        let x = first.init();
+        let y = second.init();
+        let register = |builder: &mut Builder<u32>, node: Node<u32>| {
+            let table = builder.add_table("bits", 1, vec![vec![0], vec![1]]).unwrap();
+            let gate = builder.add_gate("zero", 1, vec![(1, vec![0])]).unwrap();
+            let constraint = builder.assert_lookup(vec![node], table).unwrap();
+            (table, gate, constraint)
+        };
+        let (table, gate, constraint) = register(&mut first, x.clone());
+        let _ = register(&mut second, y.clone());
+        assert_eq!(table.index(), 0);
+        assert_eq!(gate.index(), 0);
+
+        assert_eq!(second.table(table).unwrap_err(), GraphError::ForeignTable { table: 0 });
+        assert_eq!(second.assert_lookup(vec![y.clone()], table).unwrap_err(), GraphError::ForeignTable { table: 0 });
+        assert_eq!(second.gate(gate).unwrap_err(), GraphError::ForeignGate { gate: 0 });
+        assert_eq!(second.assert_gate(gate, vec![y.clone()]).unwrap_err(), GraphError::ForeignGate { gate: 0 });
+        assert_eq!(second.label(constraint, "x is a bit").unwrap_err(), GraphError::ForeignConstraint { constraint: 0 });
+        assert_eq!(
+            GraphError::ForeignTable { table: 0 }.to_string(),
+            "Table 0 belongs to a different builder"
+        );
+
+        // Rejected calls leave the second builder as it was
+        assert_eq!(second.constraints.len(), 1);
+        assert_eq!(second.constraints[0].label, None);
+        first.label(constraint, "x is a bit").unwrap();
+        assert_eq!(first.constraints[0].label.as_deref(), Some("x is a bit"));
+    }
+
+    #[test]
+    fn test_adopted_tables_and_gates() {
+        // x is fixed to 1 ahead of time; y is then constrained to a bit with x + y = 1
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let bits = builder.add_table("bits", 1, vec![vec![0], vec![1]]).unwrap();
+        let sum_is_one = builder.add_gate("sum is one", 2, vec![(1, vec![0]), (1, vec![1]), (u32::MAX, vec![])]).unwrap();
+
+        let mut specialised = builder.partial_eval(HashMap::from([(x.id, 1)]), OverflowMode::Wrapping).unwrap();
+        let (x, y) = (specialised.adopt(&x).unwrap(), specialised.adopt(&y).unwrap());
+        assert_eq!(specialised.assert_lookup(vec![y.clone()], bits).unwrap_err(), GraphError::ForeignTable { table: 0 });
+        assert_eq!(specialised.assert_gate(sum_is_one, vec![x.clone(), y.clone()]).unwrap_err(), GraphError::ForeignGate { gate: 0 });
+
+        let adopted_bits = specialised.adopt_table(bits).unwrap();
+        let adopted_gate = specialised.adopt_gate(sum_is_one).unwrap();
+        assert_eq!(specialised.adopt_table(adopted_bits).unwrap(), adopted_bits);
+        assert_eq!(specialised.table(adopted_bits).unwrap().name(), "bits");
+        specialised.assert_lookup(vec![y.clone()], adopted_bits).unwrap();
+        specialised.assert_gate(adopted_gate, vec![x, y.clone()]).unwrap();
+
+        let check = |y_value| specialised.check_constraints(&specialised.fill_nodes(HashMap::from([(y.id, y_value)])).unwrap());
+        assert!(check(0));
+        assert!(!check(1));
+        assert!(!check(2));
+
+        // Registrations the original makes afterwards are not part of the copy
+        let later_table = builder.add_table("later", 1, Vec::new()).unwrap();
+        let later_gate = builder.add_gate("later", 0, Vec::new()).unwrap();
+        assert_eq!(specialised.adopt_table(later_table).unwrap_err(), GraphError::ForeignTable { table: 1 });
+        assert_eq!(specialised.adopt_gate(later_gate).unwrap_err(), GraphError::ForeignGate { gate: 1 });
+        assert_eq!(Builder::<u32>::new().adopt_gate(sum_is_one).unwrap_err(), GraphError::ForeignGate { gate: 0 });
+    }
+
+    #[test]
+    fn test_hint_failures() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let halved = builder
+            .try_hint(vec![x.clone()], |values| match values[0] % 2 {
+                0 => Ok(values[0] / 2),
+                _ => Err(format!("{} is odd", values[0])),
+            })
+            .unwrap();
+        let _quotient = builder.hint(vec![halved.clone()], |values| 100 / values[1]).unwrap();
+        let _missing = builder.hint(vec![x.clone()], |values| *values.get(7).expect("no node 7")).unwrap();
+
+        let failure = |x_value: u32| builder.fill_nodes(HashMap::from([(x.id, x_value)])).unwrap_err();
+        let program = builder.compile().unwrap();
+
+        let error = failure(3);
+        assert_eq!(
+            error,
+            GraphError::HintFailed { node: 1, hint: 0, message: "3 is odd".to_string() }
+        );
+        assert_eq!(error.to_string(), "Hint 0 failed at node 1: 3 is odd");
+
+        // Panics with a static and a formatted message
+        assert_eq!(
+            failure(0),
+            GraphError::HintFailed { node: 2, hint: 1, message: "attempt to divide by zero".to_string() }
+        );
+        assert_eq!(
+            failure(4),
+            GraphError::HintFailed { node: 3, hint: 2, message: "no node 7".to_string() }
+        );
+        assert_eq!(
+            program.run(HashMap::from([(x.id, 0)]), OverflowMode::Wrapping).unwrap_err(),
+            failure(0)
+        );
+    }
+
+    #[test]
+    fn test_unknown_nodes() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let doubled = builder.add(x.clone(), x.clone()).unwrap();
+
+        let error = builder.fill_nodes(HashMap::from([(x.id, 1), (7, 2)])).unwrap_err();
+        assert_eq!(error, GraphError::UnknownNode { node: 7 });
+        assert_eq!(error.to_string(), "Node 7 is not part of the graph");
+
+        // An operand id no node was ever given
+        builder.nodes[doubled.id].node_type = NodeType::Add(x.id, 9);
+        let error = builder.fill_nodes(HashMap::from([(x.id, 1)])).unwrap_err();
+        assert_eq!(error, GraphError::UnknownNode { node: 9 });
+    }
 }
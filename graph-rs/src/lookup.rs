@@ -0,0 +1,198 @@
+use crate::constraint::ConstraintKind;
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::{Builder, ConstraintId, Node};
+
+/// A fixed table of rows that lookup constraints check tuples of nodes against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table<V: Value> {
+    name: String,
+    width: usize,
+    rows: Vec<Vec<V>>,
+}
+
+impl<V: Value> Table<V> {
+    /// Creates a table whose rows all have `width` entries.
+    pub(crate) fn new(index: usize, name: &str, width: usize, rows: Vec<Vec<V>>) -> Result<Self, GraphError> {
+        if let Some(row) = rows.iter().find(|row| row.len() != width) {
+            return Err(GraphError::TableWidth {
+                table: index,
+                expected: width,
+                found: row.len(),
+            });
+        }
+        Ok(Table {
+            name: name.to_string(),
+            width,
+            rows,
+        })
+    }
+
+    /// Returns the name the table was registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of entries in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the rows of the table.
+    pub fn rows(&self) -> &[Vec<V>] {
+        &self.rows
+    }
+
+    /// Returns true if `tuple` is one of the rows.
+    pub fn contains(&self, tuple: &[&V]) -> bool {
+        self.rows
+            .iter()
+            .any(|row| row.len() == tuple.len() && row.iter().zip(tuple).all(|(a, b)| a == *b))
+    }
+}
+
+/// A handle to a table registered with `Builder::add_table`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableId {
+    index: usize,
+    // The id of the builder that registered the table
+    builder: usize,
+}
+
+impl TableId {
+    /// Returns the position of the table in the builder's registry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Registers a table for lookup constraints. Every row must have `width` entries.
+    pub fn add_table(&mut self, name: &str, width: usize, rows: Vec<Vec<V>>) -> Result<TableId, GraphError> {
+        let index = self.tables.len();
+        self.tables.push(Table::new(index, name, width, rows)?);
+        Ok(TableId {
+            index,
+            builder: self.id,
+        })
+    }
+
+    /// Returns a registered table.
+    pub fn table(&self, table: TableId) -> Result<&Table<V>, GraphError> {
+        if table.builder != self.id {
+            return Err(GraphError::ForeignTable { table: table.index });
+        }
+        self.tables.get(table.index).ok_or(GraphError::UnknownTable { table: table.index })
+    }
+
+    /// Returns this builder's handle for `table`, which may also come from the
+    /// builder this one was derived from, as `adopt` does for nodes. Tables the
+    /// original registered after the copy was made fail with `GraphError::ForeignTable`.
+    pub fn adopt_table(&self, table: TableId) -> Result<TableId, GraphError> {
+        match self.parent {
+            _ if table.builder == self.id => self.table(table).map(|_| table),
+            Some(parent) if table.builder == parent.id && table.index < parent.tables => Ok(TableId {
+                index: table.index,
+                builder: self.id,
+            }),
+            _ => Err(GraphError::ForeignTable { table: table.index }),
+        }
+    }
+
+    /// Asserts that the values of `nodes`, in order, form a row of `table`.
+    ///
+    /// The constraint is kept as a lookup rather than expanded into
+    /// arithmetic, so exporters can emit it as a lookup argument.
+    pub fn assert_lookup(&mut self, nodes: Vec<Node<V>>, table: TableId) -> Result<ConstraintId, GraphError> {
+        let width = self.table(table)?.width;
+        if nodes.len() != width {
+            return Err(GraphError::TableWidth {
+                table: table.index,
+                expected: width,
+                found: nodes.len(),
+            });
+        }
+        let nodes = nodes
+            .iter()
+            .map(|node| self.check(node))
+            .collect::<Result<_, _>>()?;
+        Ok(self.push_constraint(
+            ConstraintKind::Lookup {
+                table: table.index,
+                nodes,
+            },
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{BabyBear, Fp, OverflowMode, Program};
+
+    type F = Fp<BabyBear>;
+
+    /// A 2-bit S-box: x -> (3x + 1) mod 4
+    fn sbox() -> Vec<Vec<F>> {
+        (0..4).map(|x| vec![Fp::new(x), Fp::new((3 * x + 1) % 4)]).collect()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let y = builder.hint(vec![x.clone()], |values| Fp::new((3 * values[0].value() + 1) % 4)).unwrap();
+        let table = builder.add_table("sbox", 2, sbox()).unwrap();
+        builder.assert_lookup(vec![x.clone(), y.clone()], table).unwrap();
+        assert_eq!(builder.table(table).unwrap().name(), "sbox");
+
+        for x_value in 0..4 {
+            let values = builder.fill_nodes(HashMap::from([(x.id(), Fp::new(x_value))])).unwrap();
+            assert!(builder.check_constraints(&values));
+        }
+
+        let mut values = builder.fill_nodes(HashMap::from([(x.id(), Fp::new(2))])).unwrap();
+        values.insert(y.id(), Fp::new(2));
+        let violations = builder.constraint_violations(&values);
+        assert_eq!(violations[0].to_string(), "Constraint 0 violated: (node 0 = 2, node 1 = 2) not in table 0");
+        let values = builder.fill_nodes(HashMap::from([(x.id(), Fp::new(4))])).unwrap();
+        assert!(!builder.check_constraints(&values));
+
+        // Lookups are not expanded by lowering, and survive compilation
+        let lowered = builder.lower_constraints().unwrap();
+        assert_eq!(
+            lowered.constraints().collect::<Vec<_>>(),
+            vec![&ConstraintKind::Lookup { table: 0, nodes: vec![0, 1] }]
+        );
+        let program = Program::from_bytes(&builder.compile().unwrap().to_bytes(), &builder).unwrap();
+        let mut witness = program.run(HashMap::from([(x.id(), Fp::new(3))]), OverflowMode::Wrapping).unwrap();
+        assert!(program.check_constraints(&witness));
+        witness.insert(y.id(), Fp::new(3));
+        assert!(!program.check_constraints(&witness));
+    }
+
+    #[test]
+    fn test_widths_and_empty_tables() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+
+        assert_eq!(
+            builder.add_table("ragged", 2, vec![vec![0, 1], vec![2]]).unwrap_err(),
+            GraphError::TableWidth { table: 0, expected: 2, found: 1 }
+        );
+        let bytes = builder.add_table("bytes", 1, (0..256).map(|byte| vec![byte]).collect()).unwrap();
+        assert_eq!(
+            builder.assert_lookup(vec![x.clone(), x.clone()], bytes).unwrap_err(),
+            GraphError::TableWidth { table: 0, expected: 1, found: 2 }
+        );
+
+        // No tuple is a row of an empty table
+        let empty = builder.add_table("empty", 1, Vec::new()).unwrap();
+        builder.assert_lookup(vec![x.clone()], empty).unwrap();
+        let values = builder.fill_nodes(HashMap::from([(x.id(), 0)])).unwrap();
+        assert!(!builder.check_constraints(&values));
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, NodeType, OverflowMode};
+
+/// The kind of operation a node performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// An input node whose value is supplied by the caller.
+    Input,
+    /// A constant node with a fixed value.
+    Constant,
+    /// A node that adds two other nodes.
+    Add,
+    /// A node that subtracts the second node from the first.
+    Sub,
+    /// A node that multiplies two other nodes.
+    Mul,
+    /// A node that negates another node.
+    Neg,
+    /// A node whose value is computed by a hint function.
+    Hint,
+}
+
+/// A node that has just been evaluated.
+#[derive(Debug)]
+pub struct NodeEvent<'a, V: Value> {
+    /// The id of the node.
+    pub node: usize,
+    /// What the node computes.
+    pub kind: NodeKind,
+    /// The id and value of each operand, in operand order. Hints list their dependencies.
+    pub operands: Vec<(usize, &'a V)>,
+    /// The value the node evaluated to.
+    pub result: &'a V,
+}
+
+/// Receives a callback for every node as the graph is evaluated.
+///
+/// Closures taking a `&NodeEvent` are observers, so a tracer can be as
+/// small as `|event| println!("{:?}", event)`.
+pub trait Observer<V: Value> {
+    /// Called after a node is evaluated, in evaluation order. Inputs are reported too.
+    fn on_node(&mut self, event: &NodeEvent<'_, V>);
+
+    /// Called when a node fails to evaluate, before the error is returned.
+    fn on_error(&mut self, _node: usize, _error: &GraphError) {}
+}
+
+impl<V: Value, F: FnMut(&NodeEvent<'_, V>)> Observer<V> for F {
+    fn on_node(&mut self, event: &NodeEvent<'_, V>) {
+        self(event)
+    }
+}
+
+/// An observer that prints every node and error to standard output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Printer;
+
+impl<V: Value> Observer<V> for Printer {
+    fn on_node(&mut self, event: &NodeEvent<'_, V>) {
+        let operands: Vec<String> = event
+            .operands
+            .iter()
+            .map(|(id, value)| format!("{} = {}", id, value))
+            .collect();
+        println!("Node {} ({:?}): [{}] -> {}", event.node, event.kind, operands.join(", "), event.result);
+    }
+
+    fn on_error(&mut self, _node: usize, error: &GraphError) {
+        println!("{}", error);
+    }
+}
+
+impl<V: Value> NodeType<V> {
+    /// Returns the kind of this node.
+    fn kind(&self) -> NodeKind {
+        match self {
+            NodeType::Input => NodeKind::Input,
+            NodeType::Constant(_) => NodeKind::Constant,
+            NodeType::Add(..) => NodeKind::Add,
+            NodeType::Sub(..) => NodeKind::Sub,
+            NodeType::Mul(..) => NodeKind::Mul,
+            NodeType::Neg(_) => NodeKind::Neg,
+            NodeType::Hint(..) => NodeKind::Hint,
+        }
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Fills in all the nodes of the graph, reporting each one to `observer`.
+    ///
+    /// Evaluates exactly like `fill_nodes_with_mode`, but prints nothing
+    /// itself; pass a `Printer` to trace to standard output.
+    pub fn fill_nodes_observed(
+        &self,
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+        observer: &mut dyn Observer<V>,
+    ) -> Result<Witness<V>, GraphError> {
+        let mut values = self.input_witness(inputs)?;
+        
+        for index in self.topological_order()? {
+            let node = &self.nodes[index];
+            if !matches!(node.node_type, NodeType::Input) {
+                match self.eval_node(node, &values, mode) {
+                    Ok(value) => {
+                        values.insert(node.id, value);
+                    }
+                    Err(error) => {
+                        observer.on_error(node.id, &error);
+                        return Err(error);
+                    }
+                }
+            }
+            
+            let operands = node
+                .node_type
+                .operands()
+                .into_iter()
+                .map(|id| (id, &values[id]))
+                .collect();
+            observer.on_node(&NodeEvent {
+                node: node.id,
+                kind: node.node_type.kind(),
+                operands,
+                result: &values[node.id],
+            });
+        }
+        
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_in_evaluation_order() {
+        // f(x) = -(x^2 + 5)
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let five = builder.constant(5);
+        let sum = builder.add(x_squared, five).unwrap();
+        let result = builder.neg(sum).unwrap();
+
+        let mut trace = Vec::new();
+        let values = builder
+            .fill_nodes_observed(HashMap::from([(x.id(), 3)]), OverflowMode::Wrapping, &mut |event: &NodeEvent<'_, u32>| {
+                let operands: Vec<(usize, u32)> = event.operands.iter().map(|&(id, value)| (id, *value)).collect();
+                trace.push((event.node, event.kind, operands, *event.result));
+            })
+            .unwrap();
+
+        assert_eq!(values, builder.fill_nodes(HashMap::from([(x.id(), 3)])).unwrap());
+        assert_eq!(values[&result], 14u32.wrapping_neg());
+        assert_eq!(
+            trace,
+            vec![
+                (0, NodeKind::Input, vec![], 3),
+                (1, NodeKind::Mul, vec![(0, 3), (0, 3)], 9),
+                (2, NodeKind::Constant, vec![], 5),
+                (3, NodeKind::Add, vec![(1, 9), (2, 5)], 14),
+                (4, NodeKind::Neg, vec![(3, 14)], 14u32.wrapping_neg()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        struct Recorder {
+            seen: Vec<usize>,
+            error: Option<(usize, GraphError)>,
+        }
+
+        impl Observer<u32> for Recorder {
+            fn on_node(&mut self, event: &NodeEvent<'_, u32>) {
+                self.seen.push(event.node);
+            }
+
+            fn on_error(&mut self, node: usize, error: &GraphError) {
+                self.error = Some((node, error.clone()));
+            }
+        }
+
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let big = builder.constant(u32::MAX);
+        let sum = builder.add(x.clone(), big).unwrap();
+        let _hinted = builder.hint(vec![sum.clone()], |values| values[2] / 2).unwrap();
+
+        let mut recorder = Recorder { seen: Vec::new(), error: None };
+        let error = builder
+            .fill_nodes_observed(HashMap::from([(x.id(), 1)]), OverflowMode::Checked, &mut recorder)
+            .unwrap_err();
+
+        assert_eq!(recorder.seen, vec![0, 1]);
+        assert_eq!(recorder.error, Some((sum.id(), error)));
+    }
+}
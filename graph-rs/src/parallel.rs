@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::thread;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, NodeType, OverflowMode};
+
+/// Levels with fewer nodes per thread than this are evaluated on the calling thread.
+const MIN_NODES_PER_THREAD: usize = 32;
+
+impl<V: Value> Builder<V> {
+    /// Fills in all the nodes of the graph using up to `threads` threads.
+    ///
+    /// Nodes are grouped into dependency levels, where every node only reads
+    /// nodes from earlier levels, and the nodes of each level (hints
+    /// included) are evaluated concurrently. The result, including which
+    /// error is reported when several nodes fail, is identical to
+    /// `fill_nodes_with_mode`. Once a node fails, later levels only evaluate
+    /// nodes that come before it in the sequential order, so no hint runs
+    /// that the sequential path would not reach, other than those in the same
+    /// level as the failure. `std::thread::available_parallelism` is a
+    /// sensible choice for `threads`.
+    pub fn fill_nodes_parallel(
+        &self,
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+        threads: usize,
+    ) -> Result<Witness<V>, GraphError> {
+        let mut values = self.input_witness(inputs)?;
+        let order = self.topological_order()?;
+        
+        // Remember where each node sits in the sequential order, so that the
+        // error reported is the one the sequential path would hit first
+        let mut position = vec![0; self.nodes.len()];
+        for (i, &index) in order.iter().enumerate() {
+            position[index] = i;
+        }
+        let mut first_error: Option<(usize, GraphError)> = None;
+        
+        for mut level in self.levels(&order) {
+            // Past the first failure the sequential path would have stopped
+            if let Some((first, _)) = first_error {
+                level.retain(|&index| position[index] < first);
+            }
+            for (index, result) in self.eval_level(&level, &values, mode, threads) {
+                match result {
+                    Ok(value) => {
+                        values.insert(self.nodes[index].id, value);
+                    }
+                    Err(error) => {
+                        if first_error.as_ref().is_none_or(|(first, _)| position[index] < *first) {
+                            first_error = Some((position[index], error));
+                        }
+                    }
+                }
+            }
+        }
+        
+        match first_error {
+            Some((_, error)) => Err(error),
+            None => Ok(values),
+        }
+    }
+
+    /// Groups node indices by dependency level: inputs, constants and nodes
+    /// whose operands are outside the graph are level 0, and every other node
+    /// is one level above its deepest operand.
+    fn levels(&self, order: &[usize]) -> Vec<Vec<usize>> {
+        let operands = self.operand_indices();
+        let mut level_of = vec![0; self.nodes.len()];
+        let mut levels: Vec<Vec<usize>> = Vec::new();
+        
+        for &index in order {
+            let level = operands[index]
+                .iter()
+                .map(|&operand| level_of[operand] + 1)
+                .max()
+                .unwrap_or(0);
+            level_of[index] = level;
+            
+            if level == levels.len() {
+                levels.push(Vec::new());
+            }
+            levels[level].push(index);
+        }
+        
+        levels
+    }
+
+    /// Evaluates the nodes of one level against a witness holding all earlier levels.
+    fn eval_level(
+        &self,
+        level: &[usize],
+        values: &Witness<V>,
+        mode: OverflowMode,
+        threads: usize,
+    ) -> Vec<(usize, Result<V, GraphError>)> {
+        let pending: Vec<usize> = level
+            .iter()
+            .copied()
+            .filter(|&index| !matches!(self.nodes[index].node_type, NodeType::Input))
+            .collect();
+        let eval_chunk = |chunk: &[usize]| -> Vec<(usize, Result<V, GraphError>)> {
+            chunk
+                .iter()
+                .map(|&index| (index, self.eval_node(&self.nodes[index], values, mode)))
+                .collect()
+        };
+        
+        let workers = threads.min(pending.len() / MIN_NODES_PER_THREAD);
+        if workers <= 1 {
+            return eval_chunk(&pending);
+        }
+        
+        let chunk_size = pending.len().div_ceil(workers);
+        thread::scope(|scope| {
+            let handles: Vec<_> = pending
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || eval_chunk(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{Fp, Goldilocks};
+
+    type F = Fp<Goldilocks>;
+
+    /// Builds a wide, deep pseudo-random graph over `inputs` input nodes.
+    fn random_graph(inputs: usize, nodes: usize, hint_calls: Arc<AtomicUsize>) -> Builder<F> {
+        let mut builder = Builder::new();
+        let mut created: Vec<_> = (0..inputs).map(|_| builder.init()).collect();
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        
+        for _ in 0..nodes {
+            let a = created[next(created.len())].clone();
+            let b = created[next(created.len())].clone();
+            let node = match next(5) {
+                0 => builder.add(a, b).unwrap(),
+                1 => builder.sub(a, b).unwrap(),
+                2 => builder.mul(a, b).unwrap(),
+                3 => builder.neg(a).unwrap(),
+                _ => {
+                    let (a_id, b_id) = (a.id(), b.id());
+                    let calls = Arc::clone(&hint_calls);
+                    builder.hint(vec![a, b], move |values| {
+                        calls.fetch_add(1, Ordering::Relaxed);
+                        values[a_id] * values[a_id] + values[b_id]
+                    }).unwrap()
+                }
+            };
+            created.push(node);
+        }
+        builder
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let hint_calls = Arc::new(AtomicUsize::new(0));
+        let builder = random_graph(16, 5000, Arc::clone(&hint_calls));
+        let inputs: HashMap<usize, F> = (0..16).map(|id| (id, F::new(id as u64 * 31 + 7))).collect();
+
+        let sequential = builder.fill_nodes(inputs.clone()).unwrap();
+        let sequential_calls = hint_calls.swap(0, Ordering::Relaxed);
+        for threads in [1, 2, 4, 8] {
+            let parallel = builder
+                .fill_nodes_parallel(inputs.clone(), OverflowMode::Wrapping, threads)
+                .unwrap();
+            assert_eq!(parallel, sequential);
+            assert_eq!(hint_calls.swap(0, Ordering::Relaxed), sequential_calls);
+        }
+    }
+
+    #[test]
+    fn test_parallel_reports_sequential_error() {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let doubled = builder.add(x.clone(), x.clone()).unwrap();
+        let squared = builder.mul(doubled.clone(), doubled).unwrap();
+
+        // This inversion sits two levels deeper but comes first in sequential order
+        let _deep = builder.inv(squared).unwrap();
+        let _shallow = builder.inv(x.clone()).unwrap();
+
+        let inputs = HashMap::from([(x.id(), F::zero())]);
+        let sequential = builder.fill_nodes(inputs.clone()).unwrap_err();
+        let parallel = builder
+            .fill_nodes_parallel(inputs, OverflowMode::Wrapping, 4)
+            .unwrap_err();
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel, GraphError::DivisionByZero { node: 3, divisor: 2 });
+    }
+
+    #[test]
+    fn test_levels() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let five = builder.constant(5);
+        let x_squared = builder.mul(x.clone(), x.clone()).unwrap();
+        let sum = builder.add(x_squared.clone(), five.clone()).unwrap();
+        let other = builder.add(x, five).unwrap();
+
+        let order = builder.topological_order().unwrap();
+        assert_eq!(
+            builder.levels(&order),
+            vec![vec![0, 1], vec![x_squared.id(), other.id()], vec![sum.id()]]
+        );
+    }
+
+    #[test]
+    fn test_no_hints_past_the_first_error() {
+        // x / y fails for y = 0, and the counting hint is added after it, two levels deeper
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let _quotient = builder.div(x.clone(), y.clone()).unwrap();
+        let doubled = builder.add(x.clone(), x.clone()).unwrap();
+        let quadrupled = builder.add(doubled.clone(), doubled).unwrap();
+        let counter = Arc::clone(&calls);
+        let _counted = builder.hint(vec![quadrupled], move |values| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            values[0]
+        }).unwrap();
+
+        let inputs = HashMap::from([(x.id(), F::new(6)), (y.id(), F::zero())]);
+        let sequential = builder.fill_nodes(inputs.clone()).unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let parallel = builder.fill_nodes_parallel(inputs, OverflowMode::Wrapping, 4).unwrap_err();
+        assert_eq!(parallel, sequential);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, NodeType, OverflowMode};
+
+impl<V: Value> Builder<V> {
+    /// Specialises the graph to values known ahead of time for some inputs.
+    ///
+    /// Returns a new builder in which those inputs, and every node computable
+    /// from them alone, hints included, are `Constant` nodes. Node ids are
+    /// unchanged, so the remaining inputs keep their ids, and all constraints
+    /// are kept. The new builder has its own identity: pass existing `Node`
+    /// handles through `adopt` before using them with it.
+    pub fn partial_eval(
+        &self,
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+    ) -> Result<Builder<V>, GraphError> {
+        for &id in inputs.keys() {
+            if !self.nodes.iter().any(|node| node.id == id && matches!(node.node_type, NodeType::Input)) {
+                return Err(GraphError::NotAnInput { node: id });
+            }
+        }
+        
+        let mut values = Witness::with_len(self.next_id);
+        for (id, value) in inputs {
+            values.insert(id, value);
+        }
+        
+        let mut specialised = self.derive();
+        for index in self.topological_order()? {
+            let node = &self.nodes[index];
+            let known = match node.node_type {
+                NodeType::Input => values.contains(node.id),
+                _ => node.node_type.operands().iter().all(|&id| values.contains(id)),
+            };
+            if !known {
+                continue;
+            }
+            
+            if !matches!(node.node_type, NodeType::Input) {
+                let value = self.eval_node(node, &values, mode)?;
+                values.insert(node.id, value);
+            }
+            specialised.nodes[index].node_type = NodeType::Constant(values[node.id].clone());
+        }
+        
+        Ok(specialised)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Fp, Goldilocks};
+
+    type F = Fp<Goldilocks>;
+
+    #[test]
+    fn test_matches_full_evaluation() {
+        // f(scale, x) = x * (scale / 3) + scale^2, with scale known ahead of time
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<F>::new();
+        let scale = builder.init();
+        let x = builder.init();
+        let three = builder.constant(Fp::new(3));
+        let third = builder.div(scale.clone(), three).unwrap();
+        let scaled = builder.mul(x.clone(), third.clone()).unwrap();
+        let counter = Arc::clone(&calls);
+        let scale_id = scale.id();
+        let scale_squared = builder.hint(vec![scale.clone()], move |values| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            values[scale_id] * values[scale_id]
+        }).unwrap();
+        let result = builder.add(scaled.clone(), scale_squared.clone()).unwrap();
+
+        let specialised = builder.partial_eval(HashMap::from([(scale.id(), Fp::new(6))]), OverflowMode::Wrapping).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(format!("{:?}", specialised.nodes[third.id()]), "Node(6, Constant(2))");
+        assert_eq!(format!("{:?}", specialised.nodes[scale_squared.id()]), "Node(9, Constant(36))");
+        assert_eq!(format!("{:?}", specialised.nodes[scaled.id()]), "Node(8, Mul(1, 6))");
+
+        // Only x is left to supply, and no hint runs again
+        let values = specialised.fill_nodes(HashMap::from([(x.id(), Fp::new(5))])).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let expected = builder.fill_nodes(HashMap::from([(scale.id(), Fp::new(6)), (x.id(), Fp::new(5))])).unwrap();
+        assert_eq!(values, expected);
+        assert_eq!(values[&result], Fp::new(46));
+        assert!(specialised.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_constraints_are_kept() {
+        let mut builder = Builder::<u32>::new();
+        let a = builder.init();
+        let b = builder.init();
+        let sum = builder.add(a.clone(), b.clone()).unwrap();
+        let ten = builder.constant(10);
+        builder.assert_equal(sum, ten).unwrap();
+
+        let specialised = builder.partial_eval(HashMap::from([(a.id(), 3)]), OverflowMode::Wrapping).unwrap();
+        let good = specialised.fill_nodes(HashMap::from([(b.id(), 7)])).unwrap();
+        let bad = specialised.fill_nodes(HashMap::from([(b.id(), 8)])).unwrap();
+        assert!(specialised.check_constraints(&good));
+        assert!(!specialised.check_constraints(&bad));
+
+        // Nothing known leaves the graph as it was
+        let unchanged = builder.partial_eval(HashMap::new(), OverflowMode::Wrapping).unwrap();
+        assert_eq!(format!("{:?}", unchanged.nodes), format!("{:?}", builder.nodes));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let big = builder.constant(u32::MAX);
+        let sum = builder.add(x.clone(), big).unwrap();
+        let _product = builder.mul(sum.clone(), y).unwrap();
+
+        assert_eq!(
+            builder.partial_eval(HashMap::from([(sum.id(), 1)]), OverflowMode::Wrapping).unwrap_err(),
+            GraphError::NotAnInput { node: sum.id() }
+        );
+        assert!(matches!(
+            builder.partial_eval(HashMap::from([(x.id(), 1)]), OverflowMode::Checked),
+            Err(GraphError::Overflow { node: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,861 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::GraphError;
+use crate::field::{Fp, PrimeModulus};
+use crate::field256::{Fp256, PrimeModulus256};
+use crate::gate::CustomGate;
+use crate::lookup::Table;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::constraint::ConstraintKind;
+use crate::{Builder, HintFunction, NodeType, OverflowMode};
+
+/// Identifies the serialized form of a program.
+const MAGIC: &[u8; 4] = b"GRPH";
+const VERSION: u8 = 3;
+
+/// One instruction of a compiled program.
+///
+/// Registers are node ids, so a program's register file is the witness of
+/// the graph it was compiled from. Every node keeps its own register and none
+/// is reused, so the register file is as large as the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction<V: Value> {
+    /// Loads a constant into `dst`.
+    Const { dst: u32, value: V },
+    /// `dst = a + b`
+    Add { dst: u32, a: u32, b: u32 },
+    /// `dst = a - b`
+    Sub { dst: u32, a: u32, b: u32 },
+    /// `dst = a * b`
+    Mul { dst: u32, a: u32, b: u32 },
+    /// `dst = -a`
+    Neg { dst: u32, a: u32 },
+    /// Calls entry `hint` of the program's hint table; its arguments are
+    /// `args_len` registers starting at `args_start` in the argument pool.
+    CallHint { dst: u32, hint: u32, args_start: u32, args_len: u32 },
+    /// Asserts that registers `a` and `b` are equal.
+    AssertEq { a: u32, b: u32 },
+    /// Asserts that registers `a` and `b` differ.
+    AssertNe { a: u32, b: u32 },
+    /// Asserts that register `a` is zero.
+    AssertZero { a: u32 },
+    /// Asserts that register `a` is not zero.
+    AssertNonZero { a: u32 },
+    /// Asserts that register `a` is 0 or 1.
+    AssertBool { a: u32 },
+    /// Asserts that the `args_len` registers starting at `args_start` in the
+    /// argument pool form a row of entry `table` of the program's table list.
+    Lookup { table: u32, args_start: u32, args_len: u32 },
+    /// Asserts that entry `gate` of the program's gate list evaluates to zero
+    /// with its wires set to the `args_len` registers starting at `args_start`.
+    Gate { gate: u32, args_start: u32, args_len: u32 },
+}
+
+impl<V: Value> Instruction<V> {
+    /// Returns the registers named directly by the instruction, leaving out
+    /// those it reads through the argument pool.
+    fn registers(&self) -> Vec<u32> {
+        match *self {
+            Instruction::Add { dst, a, b } | Instruction::Sub { dst, a, b } | Instruction::Mul { dst, a, b } => {
+                vec![dst, a, b]
+            }
+            Instruction::Neg { dst, a } => vec![dst, a],
+            Instruction::Const { dst, .. } | Instruction::CallHint { dst, .. } => vec![dst],
+            Instruction::AssertEq { a, b } | Instruction::AssertNe { a, b } => vec![a, b],
+            Instruction::AssertZero { a } | Instruction::AssertNonZero { a } | Instruction::AssertBool { a } => vec![a],
+            Instruction::Lookup { .. } | Instruction::Gate { .. } => Vec::new(),
+        }
+    }
+}
+
+/// A graph lowered to a flat, register-based instruction tape.
+///
+/// Compiling once and running many times skips the ordering, validation and
+/// node dispatch that `fill_nodes` repeats on every call. It does not save
+/// memory: there is one register per node, as described on `Instruction`.
+pub struct Program<V: Value> {
+    registers: usize,
+    inputs: Vec<u32>,
+    instructions: Vec<Instruction<V>>,
+    args: Vec<u32>,
+    hints: Vec<Arc<HintFunction<V>>>,
+    tables: Vec<Table<V>>,
+    gates: Vec<CustomGate<V>>,
+}
+
+impl<V: Value> Builder<V> {
+    /// Compiles the graph into a `Program` that evaluates to the same witness as `fill_nodes`.
+    pub fn compile(&self) -> Result<Program<V>, GraphError> {
+        let order = self.topological_order()?;
+        let known: Vec<bool> = {
+            let mut known = vec![false; self.next_id];
+            for node in &self.nodes {
+                known[node.id] = true;
+            }
+            known
+        };
+        let register = |id: usize| -> Result<u32, GraphError> {
+            if known.get(id).copied().unwrap_or(false) {
+                Ok(id as u32)
+            } else {
+                Err(GraphError::UnknownNode { node: id })
+            }
+        };
+        
+        let mut program = Program {
+            registers: self.next_id,
+            inputs: Vec::new(),
+            instructions: Vec::with_capacity(self.nodes.len() + self.constraints.len()),
+            args: Vec::new(),
+            hints: Vec::new(),
+            tables: self.tables.clone(),
+            gates: self.gates.clone(),
+        };
+        let mut hint_slots: HashMap<usize, u32> = HashMap::new();
+        
+        for index in order {
+            let node = &self.nodes[index];
+            let dst = register(node.id)?;
+            let instruction = match &node.node_type {
+                NodeType::Input => {
+                    program.inputs.push(dst);
+                    continue;
+                }
+                NodeType::Constant(value) => Instruction::Const {
+                    dst,
+                    value: value.clone(),
+                },
+                NodeType::Add(a, b) => Instruction::Add { dst, a: register(*a)?, b: register(*b)? },
+                NodeType::Sub(a, b) => Instruction::Sub { dst, a: register(*a)?, b: register(*b)? },
+                NodeType::Mul(a, b) => Instruction::Mul { dst, a: register(*a)?, b: register(*b)? },
+                NodeType::Neg(a) => Instruction::Neg { dst, a: register(*a)? },
+                NodeType::Hint(deps, func) => {
+                    let hint = *hint_slots.entry(func.id).or_insert_with(|| {
+                        program.hints.push(Arc::clone(func));
+                        (program.hints.len() - 1) as u32
+                    });
+                    let args_start = program.args.len() as u32;
+                    for dep in deps {
+                        program.args.push(register(*dep)?);
+                    }
+                    Instruction::CallHint {
+                        dst,
+                        hint,
+                        args_start,
+                        args_len: deps.len() as u32,
+                    }
+                }
+            };
+            program.instructions.push(instruction);
+        }
+        
+        for constraint in &self.constraints {
+            program.instructions.push(match &constraint.kind {
+                ConstraintKind::Equal(a, b) => Instruction::AssertEq { a: register(*a)?, b: register(*b)? },
+                ConstraintKind::NotEqual(a, b) => Instruction::AssertNe { a: register(*a)?, b: register(*b)? },
+                ConstraintKind::Zero(a) => Instruction::AssertZero { a: register(*a)? },
+                ConstraintKind::NonZero(a) => Instruction::AssertNonZero { a: register(*a)? },
+                ConstraintKind::Bool(a) => Instruction::AssertBool { a: register(*a)? },
+                ConstraintKind::Lookup { table, nodes } => {
+                    let args_start = program.args.len() as u32;
+                    for node in nodes {
+                        program.args.push(register(*node)?);
+                    }
+                    Instruction::Lookup {
+                        table: *table as u32,
+                        args_start,
+                        args_len: nodes.len() as u32,
+                    }
+                }
+                ConstraintKind::Gate { gate, nodes } => {
+                    let args_start = program.args.len() as u32;
+                    for node in nodes {
+                        program.args.push(register(*node)?);
+                    }
+                    Instruction::Gate {
+                        gate: *gate as u32,
+                        args_start,
+                        args_len: nodes.len() as u32,
+                    }
+                }
+            });
+        }
+        
+        Ok(program)
+    }
+}
+
+impl<V: Value> Program<V> {
+    /// Returns the instruction tape.
+    pub fn instructions(&self) -> &[Instruction<V>] {
+        &self.instructions
+    }
+
+    /// Returns the registers that must be supplied as inputs.
+    pub fn inputs(&self) -> &[u32] {
+        &self.inputs
+    }
+
+    /// Runs the program, returning the witness `fill_nodes_with_mode` would.
+    /// Assertions are not evaluated here; see `check_constraints`.
+    pub fn run(&self, inputs: HashMap<usize, V>, mode: OverflowMode) -> Result<Witness<V>, GraphError> {
+        for &input in &self.inputs {
+            if !inputs.contains_key(&(input as usize)) {
+                return Err(GraphError::MissingInput { node: input as usize });
+            }
+        }
+        let mut registers = Witness::with_len(self.registers);
+        for (id, value) in inputs {
+            if id >= self.registers {
+                return Err(GraphError::UnknownNode { node: id });
+            }
+            registers.insert(id, value);
+        }
+        
+        for instruction in &self.instructions {
+            let (dst, value) = match instruction {
+                Instruction::Const { dst, value } => (*dst, value.clone()),
+                Instruction::Add { dst, a, b } => {
+                    let (a, b) = (read(&registers, *dst, *a)?, read(&registers, *dst, *b)?);
+                    (*dst, mode.add(*dst as usize, a, b)?)
+                }
+                Instruction::Sub { dst, a, b } => {
+                    let (a, b) = (read(&registers, *dst, *a)?, read(&registers, *dst, *b)?);
+                    (*dst, mode.sub(*dst as usize, a, b)?)
+                }
+                Instruction::Mul { dst, a, b } => {
+                    let (a, b) = (read(&registers, *dst, *a)?, read(&registers, *dst, *b)?);
+                    (*dst, mode.mul(*dst as usize, a, b)?)
+                }
+                Instruction::Neg { dst, a } => {
+                    let a = read(&registers, *dst, *a)?;
+                    (*dst, mode.neg(*dst as usize, a)?)
+                }
+                Instruction::CallHint { dst, hint, args_start, args_len } => {
+                    let start = *args_start as usize;
+                    for &arg in &self.args[start..start + *args_len as usize] {
+                        read(&registers, *dst, arg)?;
+                    }
+                    (*dst, self.hints[*hint as usize].call(*dst as usize, &registers)?)
+                }
+                Instruction::AssertEq { .. }
+                | Instruction::AssertNe { .. }
+                | Instruction::AssertZero { .. }
+                | Instruction::AssertNonZero { .. }
+                | Instruction::AssertBool { .. }
+                | Instruction::Lookup { .. }
+                | Instruction::Gate { .. } => continue,
+            };
+            registers.insert(dst as usize, value);
+        }
+        
+        Ok(registers)
+    }
+
+    /// Checks the assertions against a witness.
+    pub fn check_constraints(&self, witness: &Witness<V>) -> bool {
+        self.instructions.iter().all(|instruction| {
+            let kind = match *instruction {
+                Instruction::Lookup { table, args_start, args_len } => {
+                    let start = args_start as usize;
+                    let nodes = self.args[start..start + args_len as usize]
+                        .iter()
+                        .map(|&arg| arg as usize)
+                        .collect();
+                    ConstraintKind::Lookup { table: table as usize, nodes }
+                }
+                Instruction::Gate { gate, args_start, args_len } => {
+                    let start = args_start as usize;
+                    let nodes = self.args[start..start + args_len as usize]
+                        .iter()
+                        .map(|&arg| arg as usize)
+                        .collect();
+                    ConstraintKind::Gate { gate: gate as usize, nodes }
+                }
+                Instruction::AssertEq { a, b } => ConstraintKind::Equal(a as usize, b as usize),
+                Instruction::AssertNe { a, b } => ConstraintKind::NotEqual(a as usize, b as usize),
+                Instruction::AssertZero { a } => ConstraintKind::Zero(a as usize),
+                Instruction::AssertNonZero { a } => ConstraintKind::NonZero(a as usize),
+                Instruction::AssertBool { a } => ConstraintKind::Bool(a as usize),
+                _ => return true,
+            };
+            kind.holds(witness, &self.tables, &self.gates)
+        })
+    }
+}
+
+/// Reads register `src` for the instruction writing `dst`.
+fn read<V: Value>(registers: &Witness<V>, dst: u32, src: u32) -> Result<&V, GraphError> {
+    registers.get(src as usize).ok_or(GraphError::MissingOperand {
+        node: dst as usize,
+        operand: src as usize,
+    })
+}
+
+/// A value with a fixed binary encoding, so programs over it can be serialized.
+pub trait Encode: Value {
+    /// Appends the encoding of this value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the start of `bytes`, returning it and the number of bytes read.
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)>;
+}
+
+impl Encode for u32 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        Some((u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?), 4))
+    }
+}
+
+impl Encode for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        Some((u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?), 8))
+    }
+}
+
+impl Encode for f64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.to_bits().encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        u64::decode(bytes).map(|(bits, read)| (f64::from_bits(bits), read))
+    }
+}
+
+impl<M: PrimeModulus> Encode for Fp<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.value().encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        let (value, read) = u64::decode(bytes)?;
+        (value < M::MODULUS).then(|| (Fp::new(value), read))
+    }
+}
+
+impl<P: PrimeModulus256> Encode for Fp256<P> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for limb in self.to_canonical() {
+            limb.encode(out);
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::decode(bytes.get(i * 8..)?)?.0;
+        }
+        Fp256::from_canonical(limbs).map(|value| (value, 32))
+    }
+}
+
+impl<V: Encode> Program<V> {
+    /// Serializes the program. Hint functions cannot be serialized, so only
+    /// their ids are written; `from_bytes` reattaches them from the builder.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        
+        let write = |out: &mut Vec<u8>, value: u32| value.encode(out);
+        write(&mut out, self.registers as u32);
+        write(&mut out, self.inputs.len() as u32);
+        for &input in &self.inputs {
+            write(&mut out, input);
+        }
+        write(&mut out, self.hints.len() as u32);
+        for hint in &self.hints {
+            write(&mut out, hint.id as u32);
+        }
+        write(&mut out, self.args.len() as u32);
+        for &arg in &self.args {
+            write(&mut out, arg);
+        }
+        write(&mut out, self.tables.len() as u32);
+        for table in &self.tables {
+            write(&mut out, table.name().len() as u32);
+            out.extend_from_slice(table.name().as_bytes());
+            write(&mut out, table.width() as u32);
+            write(&mut out, table.rows().len() as u32);
+            for value in table.rows().iter().flatten() {
+                value.encode(&mut out);
+            }
+        }
+        write(&mut out, self.gates.len() as u32);
+        for gate in &self.gates {
+            write(&mut out, gate.name().len() as u32);
+            out.extend_from_slice(gate.name().as_bytes());
+            write(&mut out, gate.wires() as u32);
+            write(&mut out, gate.terms().len() as u32);
+            for (coefficient, wires) in gate.terms() {
+                coefficient.encode(&mut out);
+                write(&mut out, wires.len() as u32);
+                for &wire in wires {
+                    write(&mut out, wire as u32);
+                }
+            }
+        }
+        
+        write(&mut out, self.instructions.len() as u32);
+        for instruction in &self.instructions {
+            match instruction {
+                Instruction::Const { dst, value } => {
+                    out.push(0);
+                    write(&mut out, *dst);
+                    value.encode(&mut out);
+                }
+                Instruction::Add { dst, a, b } => {
+                    out.push(1);
+                    for field in [dst, a, b] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::Sub { dst, a, b } => {
+                    out.push(2);
+                    for field in [dst, a, b] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::Mul { dst, a, b } => {
+                    out.push(3);
+                    for field in [dst, a, b] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::Neg { dst, a } => {
+                    out.push(4);
+                    for field in [dst, a] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::CallHint { dst, hint, args_start, args_len } => {
+                    out.push(5);
+                    for field in [dst, hint, args_start, args_len] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::AssertEq { a, b } => {
+                    out.push(6);
+                    for field in [a, b] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::AssertNe { a, b } => {
+                    out.push(7);
+                    for field in [a, b] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::AssertZero { a } => {
+                    out.push(8);
+                    write(&mut out, *a);
+                }
+                Instruction::AssertNonZero { a } => {
+                    out.push(9);
+                    write(&mut out, *a);
+                }
+                Instruction::AssertBool { a } => {
+                    out.push(10);
+                    write(&mut out, *a);
+                }
+                Instruction::Lookup { table, args_start, args_len } => {
+                    out.push(11);
+                    for field in [table, args_start, args_len] {
+                        write(&mut out, *field);
+                    }
+                }
+                Instruction::Gate { gate, args_start, args_len } => {
+                    out.push(12);
+                    for field in [gate, args_start, args_len] {
+                        write(&mut out, *field);
+                    }
+                }
+            }
+        }
+        
+        out
+    }
+
+    /// Deserializes a program written by `to_bytes`, taking hint functions
+    /// from `builder`, which must be the builder the program was compiled from.
+    pub fn from_bytes(bytes: &[u8], builder: &Builder<V>) -> Result<Self, GraphError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MAGIC || reader.take(1)? != [VERSION] {
+            return Err(GraphError::InvalidProgram { offset: 0 });
+        }
+        
+        let registers = reader.u32()? as usize;
+        let offset = reader.offset;
+        let inputs = reader.u32_vec()?;
+        if inputs.iter().any(|&input| input as usize >= registers) {
+            return Err(GraphError::InvalidProgram { offset });
+        }
+        
+        let hint_functions: HashMap<usize, &Arc<HintFunction<V>>> = builder
+            .nodes
+            .iter()
+            .filter_map(|node| match &node.node_type {
+                NodeType::Hint(_, func) => Some((func.id, func)),
+                _ => None,
+            })
+            .collect();
+        let mut hints = Vec::new();
+        for hint_id in reader.u32_vec()? {
+            match hint_functions.get(&(hint_id as usize)) {
+                Some(func) => hints.push(Arc::clone(func)),
+                None => return Err(GraphError::InvalidProgram { offset: reader.offset }),
+            }
+        }
+        let offset = reader.offset;
+        let args = reader.u32_vec()?;
+        if args.iter().any(|&arg| arg as usize >= registers) {
+            return Err(GraphError::InvalidProgram { offset });
+        }
+        let mut tables = Vec::new();
+        for index in 0..reader.u32()? as usize {
+            let offset = reader.offset;
+            let name_len = reader.u32()? as usize;
+            let name = std::str::from_utf8(reader.take(name_len)?)
+                .map_err(|_| GraphError::InvalidProgram { offset })?
+                .to_string();
+            let width = reader.u32()? as usize;
+            let row_count = reader.u32()? as usize;
+            // Every value takes at least one byte, and empty rows would cost nothing to declare
+            let remaining = bytes.len() - reader.offset;
+            if (width == 0 && row_count > 0) || row_count.checked_mul(width).is_none_or(|values| values > remaining) {
+                return Err(GraphError::InvalidProgram { offset });
+            }
+            let mut rows = Vec::with_capacity(row_count);
+            for _ in 0..row_count {
+                rows.push((0..width).map(|_| reader.value()).collect::<Result<_, _>>()?);
+            }
+            tables.push(Table::new(index, &name, width, rows).map_err(|_| GraphError::InvalidProgram { offset })?);
+        }
+        let mut gates = Vec::new();
+        for index in 0..reader.u32()? as usize {
+            let offset = reader.offset;
+            let name_len = reader.u32()? as usize;
+            let name = std::str::from_utf8(reader.take(name_len)?)
+                .map_err(|_| GraphError::InvalidProgram { offset })?
+                .to_string();
+            let wires = reader.u32()? as usize;
+            let mut terms = Vec::new();
+            for _ in 0..reader.u32()? {
+                let coefficient = reader.value()?;
+                let term = reader.u32_vec()?.into_iter().map(|wire| wire as usize).collect();
+                terms.push((coefficient, term));
+            }
+            gates.push(CustomGate::new(index, &name, wires, terms).map_err(|_| GraphError::InvalidProgram { offset })?);
+        }
+        
+        // Every register is an input or written by one instruction, so a larger
+        // register file only inflates what `run` allocates
+        let count = reader.u32()?;
+        if registers > inputs.len() + count as usize {
+            return Err(GraphError::InvalidProgram { offset: MAGIC.len() + 1 });
+        }
+        let mut instructions = Vec::new();
+        for _ in 0..count {
+            let offset = reader.offset;
+            let instruction = match reader.take(1)?[0] {
+                0 => {
+                    let dst = reader.u32()?;
+                    Instruction::Const { dst, value: reader.value()? }
+                }
+                1 => Instruction::Add { dst: reader.u32()?, a: reader.u32()?, b: reader.u32()? },
+                2 => Instruction::Sub { dst: reader.u32()?, a: reader.u32()?, b: reader.u32()? },
+                3 => Instruction::Mul { dst: reader.u32()?, a: reader.u32()?, b: reader.u32()? },
+                4 => Instruction::Neg { dst: reader.u32()?, a: reader.u32()? },
+                5 => Instruction::CallHint {
+                    dst: reader.u32()?,
+                    hint: reader.u32()?,
+                    args_start: reader.u32()?,
+                    args_len: reader.u32()?,
+                },
+                6 => Instruction::AssertEq { a: reader.u32()?, b: reader.u32()? },
+                7 => Instruction::AssertNe { a: reader.u32()?, b: reader.u32()? },
+                8 => Instruction::AssertZero { a: reader.u32()? },
+                9 => Instruction::AssertNonZero { a: reader.u32()? },
+                10 => Instruction::AssertBool { a: reader.u32()? },
+                11 => Instruction::Lookup {
+                    table: reader.u32()?,
+                    args_start: reader.u32()?,
+                    args_len: reader.u32()?,
+                },
+                12 => Instruction::Gate {
+                    gate: reader.u32()?,
+                    args_start: reader.u32()?,
+                    args_len: reader.u32()?,
+                },
+                _ => return Err(GraphError::InvalidProgram { offset }),
+            };
+            
+            // Reject anything the interpreter would index out of bounds
+            let in_bounds = match &instruction {
+                Instruction::CallHint { hint, args_start, args_len, .. } => {
+                    (*hint as usize) < hints.len()
+                        && (*args_start as usize + *args_len as usize) <= args.len()
+                }
+                Instruction::Lookup { table, args_start, args_len } => {
+                    (*table as usize) < tables.len()
+                        && (*args_start as usize + *args_len as usize) <= args.len()
+                }
+                Instruction::Gate { gate, args_start, args_len } => {
+                    (*gate as usize) < gates.len()
+                        && (*args_start as usize + *args_len as usize) <= args.len()
+                }
+                _ => true,
+            } && instruction.registers().iter().all(|&register| (register as usize) < registers);
+            if !in_bounds {
+                return Err(GraphError::InvalidProgram { offset });
+            }
+            instructions.push(instruction);
+        }
+        if reader.offset != bytes.len() {
+            return Err(GraphError::InvalidProgram { offset: reader.offset });
+        }
+        
+        Ok(Program {
+            registers,
+            inputs,
+            instructions,
+            args,
+            hints,
+            tables,
+            gates,
+        })
+    }
+}
+
+/// A cursor over serialized program bytes.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], GraphError> {
+        let slice = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or(GraphError::InvalidProgram { offset: self.offset })?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, GraphError> {
+        self.value()
+    }
+
+    fn u32_vec(&mut self) -> Result<Vec<u32>, GraphError> {
+        let len = self.u32()?;
+        (0..len).map(|_| self.u32()).collect()
+    }
+
+    fn value<V: Encode>(&mut self) -> Result<V, GraphError> {
+        let (value, read) = V::decode(&self.bytes[self.offset..])
+            .ok_or(GraphError::InvalidProgram { offset: self.offset })?;
+        self.offset += read;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyBear, Bn254Scalar};
+
+    type F = Fp<BabyBear>;
+
+    /// f(x, y) = -((x * y) + (x / y) - x^2), with x^2 hinted
+    fn example() -> Builder<F> {
+        let mut builder = Builder::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_times_y = builder.mul(x.clone(), y.clone()).unwrap();
+        let x_div_y = builder.div(x.clone(), y.clone()).unwrap();
+        let sum = builder.add(x_times_y, x_div_y).unwrap();
+        let x_id = x.id();
+        let hinted = builder.hint(vec![x.clone()], move |values| values[x_id] * values[x_id]).unwrap();
+        let difference = builder.sub(sum, hinted).unwrap();
+        let _negated = builder.neg(difference).unwrap();
+        builder
+    }
+
+    #[test]
+    fn test_program_matches_fill_nodes() {
+        let builder = example();
+        let program = builder.compile().unwrap();
+
+        for (x, y) in [(10, 2), (7, 3), (0, 5)] {
+            let inputs = HashMap::from([(0, F::new(x)), (1, F::new(y))]);
+            let expected = builder.fill_nodes(inputs.clone()).unwrap();
+            let witness = program.run(inputs, OverflowMode::Wrapping).unwrap();
+            assert_eq!(witness, expected);
+            assert!(program.check_constraints(&witness));
+        }
+
+        let inputs = HashMap::from([(0, F::new(1)), (1, F::zero())]);
+        assert_eq!(
+            program.run(inputs.clone(), OverflowMode::Wrapping).unwrap_err(),
+            builder.fill_nodes(inputs).unwrap_err()
+        );
+        assert_eq!(
+            program.run(HashMap::from([(0, F::one())]), OverflowMode::Wrapping).unwrap_err(),
+            GraphError::MissingInput { node: 1 }
+        );
+    }
+
+    #[test]
+    fn test_tape_layout() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let five = builder.constant(5);
+        let sum = builder.add(x.clone(), five).unwrap();
+        builder.assert_equal(sum, x).unwrap();
+
+        let program = builder.compile().unwrap();
+        assert_eq!(program.inputs(), &[0]);
+        assert_eq!(
+            program.instructions(),
+            &[
+                Instruction::Const { dst: 1, value: 5 },
+                Instruction::Add { dst: 2, a: 0, b: 1 },
+                Instruction::AssertEq { a: 2, b: 0 },
+            ]
+        );
+
+        let witness = program.run(HashMap::from([(0, u32::MAX - 4)]), OverflowMode::Wrapping).unwrap();
+        assert_eq!(witness[2], 0);
+        assert!(!program.check_constraints(&witness));
+        assert!(matches!(
+            program.run(HashMap::from([(0, u32::MAX - 4)]), OverflowMode::Checked),
+            Err(GraphError::Overflow { node: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let builder = example();
+        let program = builder.compile().unwrap();
+        let bytes = program.to_bytes();
+        let loaded = Program::from_bytes(&bytes, &builder).unwrap();
+
+        assert_eq!(loaded.instructions(), program.instructions());
+        assert_eq!(loaded.to_bytes(), bytes);
+        let inputs = HashMap::from([(0, F::new(10)), (1, F::new(2))]);
+        assert_eq!(
+            loaded.run(inputs.clone(), OverflowMode::Wrapping).unwrap(),
+            program.run(inputs, OverflowMode::Wrapping).unwrap()
+        );
+
+        // Truncated or foreign data is rejected
+        assert!(Program::from_bytes(&bytes[..bytes.len() - 1], &builder).is_err());
+        assert!(Program::from_bytes(b"nope", &builder).is_err());
+        assert!(Program::from_bytes(&bytes, &Builder::<F>::new()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_registers() {
+        // A single `Const` writing register `dst` of a program with `registers` registers
+        let program = |registers: u32, dst: u32| {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(VERSION);
+            // Registers, then empty inputs, hints, args, tables and gates, then one instruction
+            for field in [registers, 0, 0, 0, 0, 0, 1] {
+                field.encode(&mut bytes);
+            }
+            bytes.push(0);
+            dst.encode(&mut bytes);
+            7u32.encode(&mut bytes);
+            bytes
+        };
+        let builder = Builder::<u32>::new();
+
+        let loaded = Program::from_bytes(&program(1, 0), &builder).unwrap();
+        assert_eq!(loaded.run(HashMap::new(), OverflowMode::Wrapping).unwrap()[0], 7);
+        assert_eq!(
+            Program::from_bytes(&program(1, u32::MAX), &builder).err(),
+            Some(GraphError::InvalidProgram { offset: 33 })
+        );
+        assert_eq!(
+            Program::from_bytes(&program(u32::MAX, 0), &builder).err(),
+            Some(GraphError::InvalidProgram { offset: 5 })
+        );
+    }
+
+    #[test]
+    fn test_rejects_oversized_tables() {
+        // One table of `width` columns declaring `rows` rows, followed by no data
+        let program = |width: u32, rows: u32| {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(VERSION);
+            // Registers, then empty inputs, hints and args, then one table named "t"
+            for field in [0u32, 0, 0, 0, 1, 1] {
+                field.encode(&mut bytes);
+            }
+            bytes.push(b't');
+            for field in [width, rows, 0, 0] {
+                field.encode(&mut bytes);
+            }
+            bytes
+        };
+        let builder = Builder::<u32>::new();
+
+        assert!(Program::from_bytes(&program(0, 0), &builder).is_ok());
+        for (width, rows) in [(0, 200_000_000), (0, u32::MAX), (1, u32::MAX), (u32::MAX, u32::MAX)] {
+            assert_eq!(
+                Program::from_bytes(&program(width, rows), &builder).err(),
+                Some(GraphError::InvalidProgram { offset: 25 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_constraint_kinds() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let y = builder.init();
+        builder.assert_bool(x.clone()).unwrap();
+        builder.assert_zero(y.clone()).unwrap();
+        builder.assert_nonzero(x.clone()).unwrap();
+        builder.assert_not_equal(x, y).unwrap();
+
+        let program = builder.compile().unwrap();
+        assert_eq!(
+            &program.instructions()[..2],
+            &[Instruction::AssertBool { a: 0 }, Instruction::AssertZero { a: 1 }]
+        );
+        let loaded = Program::from_bytes(&program.to_bytes(), &builder).unwrap();
+        assert_eq!(loaded.instructions(), program.instructions());
+
+        for (x, y) in [(1, 0), (0, 0), (2, 0), (1, 1)] {
+            let inputs = HashMap::from([(0, x), (1, y)]);
+            let witness = loaded.run(inputs.clone(), OverflowMode::Wrapping).unwrap();
+            assert_eq!(loaded.check_constraints(&witness), builder.check_constraints(&builder.fill_nodes(inputs).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_256_bit_constants_round_trip() {
+        let mut builder = Builder::<Fp256<Bn254Scalar>>::new();
+        let x = builder.init();
+        let minus_one = builder.constant(-Fp256::one());
+        let _product = builder.mul(x, minus_one).unwrap();
+
+        let program = builder.compile().unwrap();
+        let loaded = Program::from_bytes(&program.to_bytes(), &builder).unwrap();
+        assert_eq!(loaded.instructions(), program.instructions());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::{Builder, Node};
+
+impl<V: Value> Builder<V> {
+    /// Constrains a node to fit in `bits` bits, returning its bits, least significant first.
+    ///
+    /// Each bit is hinted from the node's value and constrained to be 0 or 1,
+    /// and the bits are recomposed and constrained to equal the node. Over a
+    /// field the recomposition would wrap once `2^bits` reaches the modulus,
+    /// so such ranges fail with `GraphError::RangeTooWide`. Evaluation fails
+    /// with `GraphError::HintFailed` for value types without a binary
+    /// representation; see `Value::bit`.
+    pub fn assert_range(&mut self, a: Node<V>, bits: u32) -> Result<Vec<Node<V>>, GraphError> {
+        let a_id = self.check(&a)?;
+        if let Some(modulus_bits) = V::modulus_bits().filter(|&modulus_bits| bits >= modulus_bits) {
+            return Err(GraphError::RangeTooWide { bits, modulus_bits });
+        }
+        if bits == 0 {
+            self.assert_zero(a)?;
+            return Ok(Vec::new());
+        }
+        
+        let mut bit_nodes = Vec::with_capacity(bits as usize);
+        for i in 0..bits {
+            let (node, hint) = (self.next_id, self.next_hint_id);
+            let bit = self.push_hint(
+                vec![a_id],
+                Box::new(move |values| match values[a_id].bit(i) {
+                    Some(true) => Ok(V::one()),
+                    Some(false) => Ok(V::zero()),
+                    None => Err(GraphError::HintFailed {
+                        node,
+                        hint,
+                        message: format!("{} has no binary representation", values[a_id]),
+                    }),
+                }),
+            );
+            self.assert_bool(bit.clone())?;
+            bit_nodes.push(bit);
+        }
+        
+        // Horner's rule from the most significant bit: sum = sum * 2 + bit
+        let two = self.constant(V::from_u64(2));
+        let mut sum = bit_nodes[bits as usize - 1].clone();
+        for bit in bit_nodes.iter().rev().skip(1) {
+            let doubled = self.mul(sum, two.clone())?;
+            sum = self.add(doubled, bit.clone())?;
+        }
+        self.assert_equal(sum, a)?;
+        
+        Ok(bit_nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{BabyBear, Bn254Scalar, Fp, Fp256};
+
+    #[test]
+    fn test_range() {
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        let bits = builder.assert_range(x.clone(), 8).unwrap();
+        assert_eq!(bits.len(), 8);
+
+        let values = builder.fill_nodes(HashMap::from([(x.id(), Fp::new(0b1010_0110))])).unwrap();
+        assert!(builder.check_constraints(&values));
+        let bit_values: Vec<u64> = bits.iter().map(|bit| values[bit].value()).collect();
+        assert_eq!(bit_values, vec![0, 1, 1, 0, 0, 1, 0, 1]);
+
+        // 256 needs a ninth bit, and -1 is a huge field element
+        for out_of_range in [Fp::new(256), -Fp::one()] {
+            let values = builder.fill_nodes(HashMap::from([(x.id(), out_of_range)])).unwrap();
+            assert!(!builder.check_constraints(&values));
+        }
+
+        // A forged bit cannot satisfy booleanity and recomposition at once
+        let mut values = builder.fill_nodes(HashMap::from([(x.id(), Fp::new(2))])).unwrap();
+        values.insert(bits[0].id(), Fp::new(2));
+        values.insert(bits[1].id(), Fp::zero());
+        let violations = builder.constraint_violations(&values);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].constraint, 0);
+    }
+
+    #[test]
+    fn test_wide_and_empty_ranges() {
+        let mut builder = Builder::<Fp256<Bn254Scalar>>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let bits = builder.assert_range(x.clone(), 130).unwrap();
+        assert!(builder.assert_range(y.clone(), 0).unwrap().is_empty());
+
+        let two_pow_129 = Fp256::from_str_radix("200000000000000000000000000000000", 16).unwrap();
+        let inputs = HashMap::from([(x.id(), two_pow_129), (y.id(), Fp256::zero())]);
+        let values = builder.fill_nodes(inputs).unwrap();
+        assert!(builder.check_constraints(&values));
+        assert_eq!(values[&bits[129]], Fp256::one());
+
+        let values = builder.fill_nodes(HashMap::from([(x.id(), Fp256::one()), (y.id(), Fp256::one())])).unwrap();
+        assert!(!builder.check_constraints(&values));
+    }
+
+    #[test]
+    fn test_too_wide_for_field() {
+        // BabyBear is a 31-bit prime, so 31 bits could recompose to p - 1 through wraparound
+        let mut builder = Builder::<Fp<BabyBear>>::new();
+        let x = builder.init();
+        assert_eq!(
+            builder.assert_range(x.clone(), 40).unwrap_err(),
+            GraphError::RangeTooWide { bits: 40, modulus_bits: 31 }
+        );
+        assert_eq!(
+            builder.assert_range(x.clone(), 31).unwrap_err(),
+            GraphError::RangeTooWide { bits: 31, modulus_bits: 31 }
+        );
+        assert!(builder.constraints().next().is_none());
+
+        let bits = builder.assert_range(x.clone(), 30).unwrap();
+        assert_eq!(bits.len(), 30);
+        let values = builder.fill_nodes(HashMap::from([(x.id(), -Fp::one())])).unwrap();
+        assert!(!builder.check_constraints(&values));
+
+        assert_eq!(Fp256::<Bn254Scalar>::modulus_bits(), Some(254));
+        assert_eq!(u32::modulus_bits(), None);
+    }
+
+    #[test]
+    fn test_no_binary_representation() {
+        let mut builder = Builder::<f64>::new();
+        let x = builder.init();
+        let _bits = builder.assert_range(x.clone(), 4).unwrap();
+
+        let error = builder.fill_nodes(HashMap::from([(x.id(), 3.0)])).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::HintFailed { node: 1, hint: 0, message: "3 has no binary representation".to_string() }
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::constraint::ConstraintKind;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::Builder;
+
+/// A constraint that does not hold for a witness.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation<V: Value> {
+    /// The position of the constraint, in the order the constraints were added.
+    pub constraint: usize,
+    /// The label given with `Builder::label`, if any.
+    pub label: Option<String>,
+    /// What the constraint requires.
+    pub kind: ConstraintKind,
+    /// The id and value of each node the constraint names, in the order
+    /// `ConstraintKind::nodes` lists them. The value is `None` if the witness has none.
+    pub values: Vec<(usize, Option<V>)>,
+}
+
+impl<V: Value> fmt::Display for Violation<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sides: Vec<String> = self
+            .values
+            .iter()
+            .map(|(id, value)| match value {
+                Some(value) => format!("node {} = {}", id, value),
+                None => format!("node {} (missing)", id),
+            })
+            .collect();
+        write!(f, "Constraint {}", self.constraint)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({})", label)?;
+        }
+        match self.kind {
+            ConstraintKind::Equal(..) => write!(f, " violated: {} != {}", sides[0], sides[1]),
+            ConstraintKind::NotEqual(..) => write!(f, " violated: {} == {}", sides[0], sides[1]),
+            ConstraintKind::Zero(_) => write!(f, " violated: {} is not zero", sides[0]),
+            ConstraintKind::NonZero(_) => write!(f, " violated: {} is zero", sides[0]),
+            ConstraintKind::Bool(_) => write!(f, " violated: {} is not 0 or 1", sides[0]),
+            ConstraintKind::Lookup { table, .. } => {
+                write!(f, " violated: ({}) not in table {}", sides.join(", "), table)
+            }
+            ConstraintKind::Gate { gate, .. } => {
+                write!(f, " violated: ({}) do not satisfy gate {}", sides.join(", "), gate)
+            }
+        }
+    }
+}
+
+impl<V: Value> Builder<V> {
+    /// Returns every constraint that does not hold for `values`, in the order
+    /// they were added. Constraints on nodes without a value count as violated.
+    pub fn constraint_violations(&self, values: &Witness<V>) -> Vec<Violation<V>> {
+        self.constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| !constraint.kind.holds(values, &self.tables, &self.gates))
+            .map(|(index, constraint)| Violation {
+                constraint: index,
+                label: constraint.label.clone(),
+                kind: constraint.kind.clone(),
+                values: constraint
+                    .kind
+                    .nodes()
+                    .into_iter()
+                    .map(|id| (id, values.get(id).cloned()))
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_violations() {
+        // x + 1 == 5 and x * 2 == 8, checked with x = 4 and a tampered witness
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let one = builder.constant(1);
+        let two = builder.constant(2);
+        let five = builder.constant(5);
+        let eight = builder.constant(8);
+        let sum = builder.add(x.clone(), one).unwrap();
+        let product = builder.mul(x.clone(), two).unwrap();
+        let sum_is_five = builder.assert_equal(sum.clone(), five.clone()).unwrap();
+        builder.label(sum_is_five, "sum is five").unwrap();
+        builder.assert_equal(product.clone(), eight.clone()).unwrap();
+
+        let mut values = builder.fill_nodes(HashMap::from([(x.id(), 4)])).unwrap();
+        assert!(builder.constraint_violations(&values).is_empty());
+
+        values.insert(product.id(), 9);
+        values.remove(sum.id());
+        let violations = builder.constraint_violations(&values);
+        assert!(!builder.check_constraints(&values));
+        assert_eq!(
+            violations,
+            vec![
+                Violation {
+                    constraint: 0,
+                    label: Some("sum is five".to_string()),
+                    kind: ConstraintKind::Equal(sum.id(), five.id()),
+                    values: vec![(sum.id(), None), (five.id(), Some(5))],
+                },
+                Violation {
+                    constraint: 1,
+                    label: None,
+                    kind: ConstraintKind::Equal(product.id(), eight.id()),
+                    values: vec![(product.id(), Some(9)), (eight.id(), Some(8))],
+                },
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "Constraint 0 (sum is five) violated: node 5 (missing) != node 3 = 5"
+        );
+        assert_eq!(violations[1].to_string(), "Constraint 1 violated: node 6 = 9 != node 4 = 8");
+    }
+
+    #[test]
+    fn test_other_kinds() {
+        let mut builder = Builder::<u32>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_is_bool = builder.assert_bool(x.clone()).unwrap();
+        builder.assert_zero(y.clone()).unwrap();
+        builder.assert_nonzero(y.clone()).unwrap();
+        let distinct = builder.assert_not_equal(x, y).unwrap();
+        builder.label(x_is_bool, "x is a flag").unwrap();
+        builder.label(distinct, "x and y differ").unwrap();
+
+        let values = builder.fill_nodes(HashMap::from([(0, 2), (1, 2)])).unwrap();
+        let messages: Vec<String> = builder
+            .constraint_violations(&values)
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Constraint 0 (x is a flag) violated: node 0 = 2 is not 0 or 1",
+                "Constraint 1 violated: node 1 = 2 is not zero",
+                "Constraint 3 (x and y differ) violated: node 0 = 2 == node 1 = 2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, NodeType, OverflowMode};
+
+/// An evaluation of a graph that keeps its witness between input changes.
+///
+/// After the initial evaluation, `update` recomputes only the nodes that
+/// depend on a changed input, and stops propagating along paths whose values
+/// come out unchanged. Hints outside that set are not re-run.
+pub struct EvalSession<'a, V: Value> {
+    builder: &'a Builder<V>,
+    mode: OverflowMode,
+    values: Witness<V>,
+    // Position of each node index in the topological order
+    position: Vec<usize>,
+    // Node indices that read each node index
+    users: Vec<Vec<usize>>,
+    index_of: HashMap<usize, usize>,
+}
+
+impl<V: Value> Builder<V> {
+    /// Evaluates the graph and keeps the result for incremental updates.
+    pub fn session(
+        &self,
+        inputs: HashMap<usize, V>,
+        mode: OverflowMode,
+    ) -> Result<EvalSession<'_, V>, GraphError> {
+        let mut values = self.input_witness(inputs)?;
+        let order = self.topological_order()?;
+        for &index in &order {
+            let node = &self.nodes[index];
+            if let NodeType::Input = node.node_type {
+                continue;
+            }
+            let value = self.eval_node(node, &values, mode)?;
+            values.insert(node.id, value);
+        }
+        
+        let mut position = vec![0; self.nodes.len()];
+        for (i, &index) in order.iter().enumerate() {
+            position[index] = i;
+        }
+        let mut users = vec![Vec::new(); self.nodes.len()];
+        for (index, operands) in self.operand_indices().into_iter().enumerate() {
+            for operand in operands {
+                users[operand].push(index);
+            }
+        }
+        let index_of = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+        
+        Ok(EvalSession {
+            builder: self,
+            mode,
+            values,
+            position,
+            users,
+            index_of,
+        })
+    }
+}
+
+impl<V: Value> EvalSession<'_, V> {
+    /// Returns the current values of all nodes.
+    pub fn witness(&self) -> &Witness<V> {
+        &self.values
+    }
+
+    /// Sets new values for some input nodes and recomputes their dependents.
+    ///
+    /// Returns the ids of every node whose value changed, inputs included, in
+    /// evaluation order. If evaluation fails the session is left as it was
+    /// before the call.
+    pub fn update(&mut self, changes: HashMap<usize, V>) -> Result<Vec<usize>, GraphError> {
+        let nodes = &self.builder.nodes;
+        for &id in changes.keys() {
+            match self.index_of.get(&id) {
+                Some(&index) if matches!(nodes[index].node_type, NodeType::Input) => {}
+                _ => return Err(GraphError::NotAnInput { node: id }),
+            }
+        }
+        
+        // Old values of everything overwritten, to roll back on failure
+        let mut undo: Vec<(usize, Option<V>)> = Vec::new();
+        let mut changed: Vec<(usize, usize)> = Vec::new();
+        
+        // Dirty nodes, smallest topological position first
+        let mut queue = BinaryHeap::new();
+        let mut queued = vec![false; nodes.len()];
+        
+        for (id, value) in changes {
+            let index = self.index_of[&id];
+            if self.values.get(id) == Some(&value) {
+                continue;
+            }
+            undo.push((id, self.values.insert(id, value)));
+            changed.push((self.position[index], id));
+            for &user in &self.users[index] {
+                if !queued[user] {
+                    queued[user] = true;
+                    queue.push(Reverse((self.position[user], user)));
+                }
+            }
+        }
+        
+        while let Some(Reverse((position, index))) = queue.pop() {
+            let node = &nodes[index];
+            let value = match self.builder.eval_node(node, &self.values, self.mode) {
+                Ok(value) => value,
+                Err(error) => {
+                    for (id, old) in undo.into_iter().rev() {
+                        match old {
+                            Some(old) => self.values.insert(id, old),
+                            None => self.values.remove(id),
+                        };
+                    }
+                    return Err(error);
+                }
+            };
+            if self.values.get(node.id) == Some(&value) {
+                continue;
+            }
+            
+            undo.push((node.id, self.values.insert(node.id, value)));
+            changed.push((position, node.id));
+            for &user in &self.users[index] {
+                if !queued[user] {
+                    queued[user] = true;
+                    queue.push(Reverse((self.position[user], user)));
+                }
+            }
+        }
+        
+        changed.sort_unstable();
+        Ok(changed.into_iter().map(|(_, id)| id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{BabyBear, Fp};
+
+    type F = Fp<BabyBear>;
+
+    #[test]
+    fn test_update_recomputes_only_dependents() {
+        // Two independent halves, each with its own hint
+        let x_calls = Arc::new(AtomicUsize::new(0));
+        let y_calls = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let x_hint = {
+            let calls = Arc::clone(&x_calls);
+            let x_id = x.id();
+            builder.hint(vec![x.clone()], move |values| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                values[x_id] + F::one()
+            }).unwrap()
+        };
+        let y_hint = {
+            let calls = Arc::clone(&y_calls);
+            let y_id = y.id();
+            builder.hint(vec![y.clone()], move |values| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                values[y_id] * F::new(2)
+            }).unwrap()
+        };
+        let total = builder.add(x_hint.clone(), y_hint.clone()).unwrap();
+
+        let mut session = builder
+            .session(HashMap::from([(x.id(), F::new(1)), (y.id(), F::new(2))]), OverflowMode::Wrapping)
+            .unwrap();
+        assert_eq!(session.witness()[&total], F::new(6));
+
+        let changed = session.update(HashMap::from([(x.id(), F::new(10))])).unwrap();
+        assert_eq!(changed, vec![x.id(), x_hint.id(), total.id()]);
+        assert_eq!(session.witness()[&total], F::new(15));
+        assert_eq!(x_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(y_calls.load(Ordering::Relaxed), 1);
+
+        let fresh = builder
+            .fill_nodes(HashMap::from([(x.id(), F::new(10)), (y.id(), F::new(2))]))
+            .unwrap();
+        assert_eq!(session.witness(), &fresh);
+    }
+
+    #[test]
+    fn test_unchanged_values_stop_propagation() {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let zero = builder.constant(F::zero());
+        let product = builder.mul(x.clone(), zero).unwrap();
+        let result = builder.add(product, x.clone()).unwrap();
+        let nine = builder.constant(F::new(9));
+        let _unrelated = builder.neg(nine).unwrap();
+
+        let mut session = builder.session(HashMap::from([(x.id(), F::new(3))]), OverflowMode::Wrapping).unwrap();
+        assert_eq!(session.update(HashMap::from([(x.id(), F::new(3))])).unwrap(), Vec::<usize>::new());
+
+        // x * 0 stays 0, so only x and the final sum change
+        let changed = session.update(HashMap::from([(x.id(), F::new(4))])).unwrap();
+        assert_eq!(changed, vec![x.id(), result.id()]);
+    }
+
+    #[test]
+    fn test_failed_update_rolls_back() {
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let y = builder.init();
+        let quotient = builder.div(x.clone(), y.clone()).unwrap();
+
+        let mut session = builder
+            .session(HashMap::from([(x.id(), F::new(6)), (y.id(), F::new(3))]), OverflowMode::Wrapping)
+            .unwrap();
+        let before = session.witness().clone();
+
+        let error = session
+            .update(HashMap::from([(x.id(), F::new(8)), (y.id(), F::zero())]))
+            .unwrap_err();
+        assert!(matches!(error, GraphError::DivisionByZero { .. }));
+        assert_eq!(session.witness(), &before);
+        assert_eq!(session.witness()[&quotient], F::new(2));
+
+        assert_eq!(
+            session.update(HashMap::from([(quotient.id(), F::one())])),
+            Err(GraphError::NotAnInput { node: quotient.id() })
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::error::GraphError;
+use crate::value::Value;
+use crate::witness::Witness;
+use crate::{Builder, Node, NodeType, OverflowMode};
+
+impl<V: Value> Builder<V> {
+    /// Fills in all the nodes of the graph while holding as few values as possible.
+    ///
+    /// Every node is evaluated, but a value is dropped right after the last
+    /// node that reads it, unless it belongs to one of `outputs` or is
+    /// referenced by a constraint. Values live in a sparse witness, so memory
+    /// follows the number of values held rather than the size of the graph.
+    /// The returned witness holds only the kept values, which is enough for
+    /// `check_constraints`.
+    pub fn fill_nodes_streaming(
+        &self,
+        inputs: HashMap<usize, V>,
+        outputs: &[Node<V>],
+        mode: OverflowMode,
+    ) -> Result<Witness<V>, GraphError> {
+        for output in outputs {
+            self.check(output)?;
+        }
+        let mut values = self.seed_witness(inputs, Witness::sparse())?;
+        let order = self.topological_order()?;
+        let operands = self.operand_indices();
+        
+        let mut keep = vec![false; self.next_id];
+        for id in outputs
+            .iter()
+            .map(|node| node.id)
+            .chain(self.constraints.iter().flat_map(|constraint| constraint.kind.nodes()))
+        {
+            if id < keep.len() {
+                keep[id] = true;
+            }
+        }
+        
+        // The position in `order` of the last node reading each node index
+        let mut last_use: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        for (position, &index) in order.iter().enumerate() {
+            for &operand in &operands[index] {
+                last_use[operand] = Some(position);
+            }
+        }
+        
+        for (position, &index) in order.iter().enumerate() {
+            let node = &self.nodes[index];
+            if !matches!(node.node_type, NodeType::Input) {
+                let value = self.eval_node(node, &values, mode)?;
+                values.insert(node.id, value);
+            }
+            
+            // Free operands read for the last time, and this node if nothing reads it
+            for &freed in operands[index].iter().chain([&index]) {
+                let id = self.nodes[freed].id;
+                if last_use[freed].is_none_or(|last| last == position) && !keep.get(id).copied().unwrap_or(false) {
+                    values.remove(id);
+                }
+            }
+        }
+        
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{Fp, Goldilocks};
+
+    type F = Fp<Goldilocks>;
+
+    #[test]
+    fn test_long_chain_stays_small() {
+        // x_{i+1} = x_i * x_i + x_i, with a hint in the middle that watches the witness storage
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut builder = Builder::<F>::new();
+        let x = builder.init();
+        let mut current = x.clone();
+        for step in 0..200 {
+            let squared = builder.mul(current.clone(), current.clone()).unwrap();
+            current = builder.add(squared, current).unwrap();
+            if step == 100 {
+                let peak = Arc::clone(&peak);
+                let id = current.id();
+                current = builder.hint(vec![current.clone()], move |values| {
+                    peak.fetch_max(values.slots(), Ordering::Relaxed);
+                    values[id]
+                }).unwrap();
+            }
+        }
+        let one = builder.constant(F::one());
+        let result = builder.add(current, one).unwrap();
+
+        let inputs = HashMap::from([(x.id(), F::new(3))]);
+        let streamed = builder
+            .fill_nodes_streaming(inputs.clone(), std::slice::from_ref(&result), OverflowMode::Wrapping)
+            .unwrap();
+        assert_eq!((streamed.len(), streamed.slots()), (1, 1));
+        assert_eq!(peak.load(Ordering::Relaxed), 1);
+
+        let full = builder.fill_nodes(inputs).unwrap();
+        assert_eq!(streamed[&result], full[&result]);
+        assert!(peak.load(Ordering::Relaxed) > 200);
+    }
+
+    #[test]
+    fn test_constraint_nodes_are_kept() {
+        // f(a) = (a+1) / 8, checking constraints without keeping everything
+        let mut builder = Builder::<F>::new();
+        let a = builder.init();
+        let one = builder.constant(F::one());
+        let b = builder.add(a.clone(), one).unwrap();
+        let eight = builder.constant(F::new(8));
+        let c = builder.div(b.clone(), eight).unwrap();
+
+        let inputs = HashMap::from([(a.id(), F::new(15))]);
+        let streamed = builder
+            .fill_nodes_streaming(inputs.clone(), std::slice::from_ref(&c), OverflowMode::Wrapping)
+            .unwrap();
+        let full = builder.fill_nodes(inputs).unwrap();
+
+        assert!(builder.check_constraints(&streamed));
+        assert_eq!(streamed[&c], F::new(2));
+        assert!(streamed.contains(b.id()));
+        assert!(!streamed.contains(a.id()));
+        assert!(streamed.len() < full.len());
+        assert_eq!(streamed.slots(), streamed.len());
+        for (id, value) in streamed.iter() {
+            assert_eq!(Some(value), full.get(id));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::value::Value;
+
+/// A symbolic value: the expression that computes a node rather than a number.
+///
+/// Evaluating a graph over `Symbolic` with `Symbolic::var` inputs yields, for
+/// every node, the formula it computes in terms of those inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Symbolic {
+    /// An integer constant.
+    Const(u64),
+    /// A named free variable.
+    Var(String),
+    /// The sum of two expressions.
+    Add(Arc<Symbolic>, Arc<Symbolic>),
+    /// The difference of two expressions.
+    Sub(Arc<Symbolic>, Arc<Symbolic>),
+    /// The product of two expressions.
+    Mul(Arc<Symbolic>, Arc<Symbolic>),
+    /// The negation of an expression.
+    Neg(Arc<Symbolic>),
+    /// An opaque function applied to arguments, e.g. the result of a hint.
+    Apply(String, Vec<Symbolic>),
+}
+
+impl Symbolic {
+    /// Creates a free variable.
+    pub fn var(name: &str) -> Self {
+        Symbolic::Var(name.to_string())
+    }
+
+    /// Applies an opaque function to arguments.
+    pub fn apply(name: &str, args: Vec<Symbolic>) -> Self {
+        Symbolic::Apply(name.to_string(), args)
+    }
+}
+
+impl Value for Symbolic {
+    fn zero() -> Self {
+        Symbolic::Const(0)
+    }
+
+    fn one() -> Self {
+        Symbolic::Const(1)
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Symbolic::Const(value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        // Drop additive identities so the formulas stay readable
+        match (self, other) {
+            (Symbolic::Const(0), _) => other.clone(),
+            (_, Symbolic::Const(0)) => self.clone(),
+            _ => Symbolic::Add(Arc::new(self.clone()), Arc::new(other.clone())),
+        }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Symbolic::Const(0), _) => other.neg(),
+            (_, Symbolic::Const(0)) => self.clone(),
+            _ => Symbolic::Sub(Arc::new(self.clone()), Arc::new(other.clone())),
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Symbolic::Const(0), _) | (_, Symbolic::Const(0)) => Symbolic::Const(0),
+            (Symbolic::Const(1), _) => other.clone(),
+            (_, Symbolic::Const(1)) => self.clone(),
+            _ => Symbolic::Mul(Arc::new(self.clone()), Arc::new(other.clone())),
+        }
+    }
+
+    fn neg(&self) -> Self {
+        match self {
+            Symbolic::Const(0) => self.clone(),
+            Symbolic::Neg(inner) => inner.as_ref().clone(),
+            _ => Symbolic::Neg(Arc::new(self.clone())),
+        }
+    }
+}
+
+impl fmt::Display for Symbolic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbolic::Const(value) => write!(f, "{}", value),
+            Symbolic::Var(name) => write!(f, "{}", name),
+            Symbolic::Add(a, b) => write!(f, "({} + {})", a, b),
+            Symbolic::Sub(a, b) => write!(f, "({} - {})", a, b),
+            Symbolic::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Symbolic::Neg(a) => write!(f, "-{}", a),
+            Symbolic::Apply(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identities_are_dropped() {
+        let x = Symbolic::var("x");
+
+        assert_eq!(x.add(&Symbolic::zero()), x);
+        assert_eq!(Symbolic::one().mul(&x), x);
+        assert_eq!(x.mul(&Symbolic::zero()), Symbolic::Const(0));
+        assert_eq!(x.sub(&Symbolic::zero()), x);
+        assert_eq!(x.neg().neg(), x);
+    }
+
+    #[test]
+    fn test_display() {
+        let x = Symbolic::var("x");
+        let y = Symbolic::var("y");
+        let expr = x.mul(&x).add(&Symbolic::apply("sqrt", vec![y, Symbolic::from_u64(7)]));
+
+        assert_eq!(expr.to_string(), "((x * x) + sqrt(y, 7))");
+        assert_eq!(Symbolic::zero().sub(&x.sub(&expr)).to_string(), "-(x - ((x * x) + sqrt(y, 7)))");
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt;
+
+/// A domain that graph values can be drawn from.
+///
+/// The builder only needs a ring-like structure: constants, addition and
+/// multiplication. Implementations decide what those mean, e.g. `u32` wraps
+/// modulo 2^32 while `Fp<M>` reduces modulo a prime.
+pub trait Value: Clone + PartialEq + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// The multiplicative identity.
+    fn one() -> Self;
+
+    /// Converts an integer into this domain.
+    fn from_u64(value: u64) -> Self;
+
+    /// Adds two values.
+    fn add(&self, other: &Self) -> Self;
+
+    /// Subtracts `other` from this value.
+    fn sub(&self, other: &Self) -> Self;
+
+    /// Multiplies two values.
+    fn mul(&self, other: &Self) -> Self;
+
+    /// Returns the additive inverse.
+    fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    /// Adds two values, or returns `None` if the sum overflows.
+    /// Domains that cannot overflow, such as fields, never fail.
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self.add(other))
+    }
+
+    /// Subtracts `other`, or returns `None` if the difference overflows.
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self.sub(other))
+    }
+
+    /// Multiplies two values, or returns `None` if the product overflows.
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self.mul(other))
+    }
+
+    /// Negates this value, or returns `None` if the result overflows.
+    fn checked_neg(&self) -> Option<Self> {
+        Some(self.neg())
+    }
+
+    /// Adds two values, clamping at the bounds of the domain.
+    fn saturating_add(&self, other: &Self) -> Self {
+        self.add(other)
+    }
+
+    /// Subtracts `other`, clamping at the bounds of the domain.
+    fn saturating_sub(&self, other: &Self) -> Self {
+        self.sub(other)
+    }
+
+    /// Multiplies two values, clamping at the bounds of the domain.
+    fn saturating_mul(&self, other: &Self) -> Self {
+        self.mul(other)
+    }
+
+    /// Negates this value, clamping at the bounds of the domain.
+    fn saturating_neg(&self) -> Self {
+        self.neg()
+    }
+
+    /// Returns bit `i` of the value's canonical unsigned binary representation,
+    /// or `None` if the domain has none. Bits past the width of the type are 0.
+    fn bit(&self, _i: u32) -> Option<bool> {
+        None
+    }
+
+    /// Returns the bit length of the modulus for prime fields, whose values
+    /// wrap below it, or `None` for domains without one.
+    fn modulus_bits() -> Option<u32> {
+        None
+    }
+}
+
+/// A value domain in which every non-zero element has a multiplicative inverse.
+pub trait Field: Value {
+    /// Returns the multiplicative inverse, or `None` for zero.
+    fn inverse(&self) -> Option<Self>;
+}
+
+impl Value for u32 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn from_u64(value: u64) -> Self {
+        value as u32
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.wrapping_add(*other)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.wrapping_sub(*other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.wrapping_mul(*other)
+    }
+
+    fn neg(&self) -> Self {
+        self.wrapping_neg()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        (*self).checked_add(*other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        (*self).checked_sub(*other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        (*self).checked_mul(*other)
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        (*self).checked_neg()
+    }
+
+    fn saturating_add(&self, other: &Self) -> Self {
+        (*self).saturating_add(*other)
+    }
+
+    fn saturating_sub(&self, other: &Self) -> Self {
+        (*self).saturating_sub(*other)
+    }
+
+    fn saturating_mul(&self, other: &Self) -> Self {
+        (*self).saturating_mul(*other)
+    }
+
+    fn saturating_neg(&self) -> Self {
+        // The only unsigned value with a representable negation is zero
+        0
+    }
+
+    fn bit(&self, i: u32) -> Option<bool> {
+        Some(self.checked_shr(i).is_some_and(|shifted| shifted & 1 == 1))
+    }
+}
+
+impl Value for u64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn from_u64(value: u64) -> Self {
+        value
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.wrapping_add(*other)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.wrapping_sub(*other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.wrapping_mul(*other)
+    }
+
+    fn neg(&self) -> Self {
+        self.wrapping_neg()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        (*self).checked_add(*other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        (*self).checked_sub(*other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        (*self).checked_mul(*other)
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        (*self).checked_neg()
+    }
+
+    fn saturating_add(&self, other: &Self) -> Self {
+        (*self).saturating_add(*other)
+    }
+
+    fn saturating_sub(&self, other: &Self) -> Self {
+        (*self).saturating_sub(*other)
+    }
+
+    fn saturating_mul(&self, other: &Self) -> Self {
+        (*self).saturating_mul(*other)
+    }
+
+    fn saturating_neg(&self) -> Self {
+        // The only unsigned value with a representable negation is zero
+        0
+    }
+
+    fn bit(&self, i: u32) -> Option<bool> {
+        Some(self.checked_shr(i).is_some_and(|shifted| shifted & 1 == 1))
+    }
+}
+
+impl Value for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn from_u64(value: u64) -> Self {
+        value as f64
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn neg(&self) -> Self {
+        -self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_rings_wrap() {
+        assert_eq!(Value::add(&u32::MAX, &2), 1);
+        assert_eq!(Value::mul(&(1u32 << 31), &2), 0);
+        assert_eq!(Value::add(&u64::MAX, &1), 0);
+        assert_eq!(Value::sub(&0u32, &1), u32::MAX);
+        assert_eq!(Value::neg(&1u64), u64::MAX);
+        assert_eq!(<u32 as Value>::from_u64(u64::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_integer_overflow_detection() {
+        assert_eq!(Value::checked_add(&u32::MAX, &1), None);
+        assert_eq!(Value::checked_mul(&(1u64 << 32), &(1 << 32)), None);
+        assert_eq!(Value::checked_neg(&0u32), Some(0));
+        assert_eq!(Value::checked_neg(&1u32), None);
+        assert_eq!(Value::saturating_sub(&1u32, &2), 0);
+        assert_eq!(Value::saturating_mul(&u64::MAX, &2), u64::MAX);
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(Value::bit(&5u32, 0), Some(true));
+        assert_eq!(Value::bit(&5u32, 1), Some(false));
+        assert_eq!(Value::bit(&u64::MAX, 63), Some(true));
+        assert_eq!(Value::bit(&u32::MAX, 32), Some(false));
+        assert_eq!(Value::bit(&1.0f64, 0), None);
+    }
+
+    #[test]
+    fn test_f64() {
+        assert_eq!(Value::add(&1.5f64, &2.25), 3.75);
+        assert_eq!(Value::mul(&1.5f64, &<f64 as Value>::from_u64(4)), 6.0);
+        assert_eq!(Value::sub(&1.5f64, &2.0), -0.5);
+        assert_eq!(Value::neg(&1.5f64), -1.5);
+    }
+}
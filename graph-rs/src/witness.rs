@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Index;
+
+use crate::value::Value;
+use crate::Node;
+
+/// The values of a graph's nodes, stored densely by node id.
+///
+/// Node ids are handed out consecutively by the builder, so a vector indexed
+/// by id replaces a hash map. Slots for nodes without a value are empty.
+/// A witness created with `sparse` instead keeps only the values it holds,
+/// for when few nodes of a large graph have a value at once. Two witnesses
+/// are equal when they hold the same values, however they are stored.
+#[derive(Clone)]
+pub struct Witness<V: Value> {
+    storage: Storage<V>,
+}
+
+/// How a witness lays out its values.
+#[derive(Clone)]
+enum Storage<V: Value> {
+    /// One slot per node id, empty for nodes without a value.
+    Dense(Vec<Option<V>>),
+    /// Only the values present, keyed by node id.
+    Sparse(BTreeMap<usize, V>),
+}
+
+impl<V: Value> Witness<V> {
+    /// Creates an empty witness.
+    pub fn new() -> Self {
+        Witness {
+            storage: Storage::Dense(Vec::new()),
+        }
+    }
+
+    /// Creates an empty witness with room for node ids below `len`.
+    pub fn with_len(len: usize) -> Self {
+        Witness {
+            storage: Storage::Dense(vec![None; len]),
+        }
+    }
+
+    /// Creates an empty witness that stores only the values inserted into it.
+    pub fn sparse() -> Self {
+        Witness {
+            storage: Storage::Sparse(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the value of a node, if it has one.
+    pub fn get(&self, id: usize) -> Option<&V> {
+        match &self.storage {
+            Storage::Dense(values) => values.get(id).and_then(Option::as_ref),
+            Storage::Sparse(values) => values.get(&id),
+        }
+    }
+
+    /// Returns true if the node has a value.
+    pub fn contains(&self, id: usize) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Sets the value of a node, returning its previous value.
+    pub fn insert(&mut self, id: usize, value: V) -> Option<V> {
+        match &mut self.storage {
+            Storage::Dense(values) => {
+                if id >= values.len() {
+                    values.resize(id + 1, None);
+                }
+                values[id].replace(value)
+            }
+            Storage::Sparse(values) => values.insert(id, value),
+        }
+    }
+
+    /// Clears the value of a node, returning it.
+    pub fn remove(&mut self, id: usize) -> Option<V> {
+        match &mut self.storage {
+            Storage::Dense(values) => values.get_mut(id).and_then(Option::take),
+            Storage::Sparse(values) => values.remove(&id),
+        }
+    }
+
+    /// Returns the number of nodes that have a value.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense(values) => values.iter().filter(|value| value.is_some()).count(),
+            Storage::Sparse(values) => values.len(),
+        }
+    }
+
+    /// Returns the number of values the witness has room for, empty slots included.
+    pub fn slots(&self) -> usize {
+        match &self.storage {
+            Storage::Dense(values) => values.len(),
+            Storage::Sparse(values) => values.len(),
+        }
+    }
+
+    /// Returns true if no node has a value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over `(node id, value)` pairs in id order, skipping nodes without a value.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &V)> {
+        let (dense, sparse) = match &self.storage {
+            Storage::Dense(values) => (Some(values), None),
+            Storage::Sparse(values) => (None, Some(values)),
+        };
+        let dense = dense.into_iter().flat_map(|values| {
+            values
+                .iter()
+                .enumerate()
+                .filter_map(|(id, value)| value.as_ref().map(|value| (id, value)))
+        });
+        let sparse = sparse.into_iter().flat_map(|values| values.iter().map(|(id, value)| (*id, value)));
+        dense.chain(sparse)
+    }
+}
+
+impl<V: Value> Default for Witness<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Value> PartialEq for Witness<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<V: Value> Index<usize> for Witness<V> {
+    type Output = V;
+
+    fn index(&self, id: usize) -> &V {
+        match self.get(id) {
+            Some(value) => value,
+            None => panic!("No value for node {} in witness", id),
+        }
+    }
+}
+
+impl<V: Value> Index<&Node<V>> for Witness<V> {
+    type Output = V;
+
+    fn index(&self, node: &Node<V>) -> &V {
+        &self[node.id()]
+    }
+}
+
+impl<V: Value> From<HashMap<usize, V>> for Witness<V> {
+    fn from(map: HashMap<usize, V>) -> Self {
+        let mut witness = Witness::new();
+        for (id, value) in map {
+            witness.insert(id, value);
+        }
+        witness
+    }
+}
+
+impl<V: Value> FromIterator<(usize, V)> for Witness<V> {
+    fn from_iter<I: IntoIterator<Item = (usize, V)>>(iter: I) -> Self {
+        let mut witness = Witness::new();
+        for (id, value) in iter {
+            witness.insert(id, value);
+        }
+        witness
+    }
+}
+
+impl<V: Value> fmt::Debug for Witness<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut witness = Witness::<u32>::with_len(2);
+        assert!(witness.is_empty());
+
+        assert_eq!(witness.insert(1, 7), None);
+        assert_eq!(witness.insert(4, 9), None);
+        assert_eq!(witness.insert(1, 8), Some(7));
+        assert_eq!(witness.get(0), None);
+        assert_eq!(witness[1], 8);
+        assert_eq!(witness.len(), 2);
+
+        assert_eq!(witness.remove(1), Some(8));
+        assert_eq!(witness.remove(10), None);
+        assert!(!witness.contains(1));
+    }
+
+    #[test]
+    fn test_iter_and_conversions() {
+        let witness: Witness<u32> = HashMap::from([(3, 30), (0, 0), (1, 10)]).into();
+
+        assert_eq!(witness.iter().collect::<Vec<_>>(), vec![(0, &0), (1, &10), (3, &30)]);
+        assert_eq!(format!("{:?}", witness), "{0: 0, 1: 10, 3: 30}");
+        assert_eq!(witness, vec![(1, 10), (3, 30), (0, 0)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_equality_ignores_empty_slots() {
+        let mut sized = Witness::<u32>::with_len(8);
+        assert_eq!(sized, Witness::new());
+
+        sized.insert(2, 20);
+        let mut grown = Witness::from(HashMap::from([(2, 20), (6, 60)]));
+        assert_ne!(sized, grown);
+        grown.remove(6);
+        assert_eq!(sized, grown);
+        assert_ne!(sized, Witness::from(HashMap::from([(2, 21)])));
+    }
+
+    #[test]
+    fn test_sparse_storage() {
+        let mut sparse = Witness::<u32>::sparse();
+        sparse.insert(1_000_000, 7);
+        sparse.insert(3, 30);
+        assert_eq!(sparse.slots(), 2);
+        assert_eq!(sparse.iter().collect::<Vec<_>>(), vec![(3, &30), (1_000_000, &7)]);
+
+        let dense: Witness<u32> = HashMap::from([(3, 30), (1_000_000, 7)]).into();
+        assert_eq!(dense.slots(), 1_000_001);
+        assert_eq!(sparse, dense);
+        assert_eq!(sparse.remove(1_000_000), Some(7));
+        assert_eq!((sparse.len(), sparse.slots()), (1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "No value for node 2 in witness")]
+    fn test_index_missing_panics() {
+        let witness = Witness::<u32>::with_len(3);
+        let _ = witness[2];
+    }
+}